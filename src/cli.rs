@@ -0,0 +1,104 @@
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: speedy [OPTIONS]
+
+Options:
+  --data-dir <DIR>  Store settings in DIR instead of the platform data directory
+  --headless        Print stats to stdout instead of opening a window
+  --interface <NAME>
+                    Only monitor this interface; repeat for more. Without it,
+                    the comma-separated list in SPEEDY_INTERFACES is used
+  --interval <SECS> Seconds between updates in headless mode (default 1)
+  --metrics-port <PORT>
+                    Serve Prometheus metrics at http://127.0.0.1:PORT/metrics
+  --metrics-bind <ADDR>
+                    Address the metrics server listens on (default 127.0.0.1);
+                    use 0.0.0.0 to expose it to the network
+  -h, --help        Print this help";
+
+#[derive(Debug, Default)]
+pub struct CliArgs {
+    pub data_dir: Option<PathBuf>,
+    pub headless: bool,
+    pub interfaces: Vec<String>, // only monitor these; empty = all
+    pub interval: Option<Duration>,
+    pub metrics_port: Option<u16>,
+    pub metrics_bind: Option<IpAddr>, // None = localhost only
+    pub help: bool,
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        // Accept both `--flag value` and `--flag=value`
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value)),
+            _ => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline_value
+                .map(str::to_string)
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("Missing value for {flag}"))
+        };
+
+        match flag.as_str() {
+            "--data-dir" => parsed.data_dir = Some(PathBuf::from(value()?)),
+            "--headless" => parsed.headless = true,
+            "--interface" => {
+                let value = value()?;
+                if value.trim().is_empty() {
+                    return Err("Empty interface name".to_string());
+                }
+                parsed.interfaces.push(value.trim().to_string());
+            }
+            "--interval" => {
+                let value = value()?;
+                let secs = value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|secs| secs.is_finite() && *secs > 0.0)
+                    .ok_or_else(|| format!("Invalid interval: {value}"))?;
+                parsed.interval = Some(Duration::from_secs_f64(secs));
+            }
+            "--metrics-port" => {
+                let value = value()?;
+                parsed.metrics_port = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid metrics port: {value}"))?,
+                );
+            }
+            "--metrics-bind" => {
+                let value = value()?;
+                parsed.metrics_bind = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid metrics address: {value}"))?,
+                );
+            }
+            "-h" | "--help" => parsed.help = true,
+            _ => return Err(format!("Unknown argument: {arg}")),
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub const INTERFACES_ENV: &str = "SPEEDY_INTERFACES";
+
+// Interface names from SPEEDY_INTERFACES, e.g. "eth0,wlan0"
+pub fn interfaces_from_env() -> Vec<String> {
+    std::env::var(INTERFACES_ENV)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,108 @@
+use crate::network_monitor::NetworkStats;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Ask the user where to save a file. Returns `None` if the dialog was
+// cancelled. Where no file dialog is available (Linux without a desktop
+// portal on the session bus) the file goes next to the executable instead.
+pub fn pick_save_path(default_name: &str, extension: &str) -> Option<PathBuf> {
+    if cfg!(target_os = "linux") && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+        return Some(crate::settings::default_export_path(default_name));
+    }
+    rfd::FileDialog::new()
+        .set_file_name(default_name)
+        .add_filter(extension.to_uppercase(), &[extension])
+        .save_file()
+}
+
+// Quote a CSV field if it contains separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Write stats as CSV with raw numbers (bytes and bytes/sec) so the file is
+// easy to process further.
+pub fn write_csv(path: &Path, stats: &[NetworkStats]) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(File::create(path)?);
+    writeln!(
+        file,
+        "name,bytes_received,bytes_transmitted,download_speed,upload_speed,is_active"
+    )?;
+    for s in stats {
+        writeln!(
+            file,
+            "{},{},{},{},{},{}",
+            csv_field(&s.name),
+            s.bytes_received,
+            s.bytes_transmitted,
+            s.raw_download_speed,
+            s.raw_upload_speed,
+            s.is_active
+        )?;
+    }
+    file.flush()
+}
+
+#[derive(Serialize)]
+struct LogRecord<'a> {
+    timestamp: f64, // seconds since the Unix epoch
+    interfaces: Vec<LogEntry<'a>>,
+}
+
+#[derive(Serialize)]
+struct LogEntry<'a> {
+    name: &'a str,
+    download_speed: f64,
+    upload_speed: f64,
+    bytes_received: u64,
+    bytes_transmitted: u64,
+}
+
+// Appends one JSON line per refresh to a log file that stays open while
+// recording.
+pub struct Recorder {
+    path: PathBuf,
+    file: File,
+}
+
+impl Recorder {
+    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { path, file })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&mut self, stats: &[NetworkStats]) -> std::io::Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let record = LogRecord {
+            timestamp,
+            interfaces: stats
+                .iter()
+                .map(|s| LogEntry {
+                    name: &s.name,
+                    download_speed: s.raw_download_speed,
+                    upload_speed: s.raw_upload_speed,
+                    bytes_received: s.bytes_received,
+                    bytes_transmitted: s.bytes_transmitted,
+                })
+                .collect(),
+        };
+        let line = serde_json::to_string(&record)?;
+        writeln!(self.file, "{line}")?;
+        // Flush every line so a crash loses at most the current sample
+        self.file.flush()
+    }
+}
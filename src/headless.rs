@@ -0,0 +1,57 @@
+// Headless mode: print stats to stdout without opening a window
+use crate::metrics;
+use crate::network_monitor::{
+    Backend, NetworkMonitor, NetworkStats, RateFormat, format_bytes, format_total_bytes,
+};
+use std::sync::mpsc;
+use std::time::Duration;
+
+pub fn run(interval: Duration, metrics: Option<metrics::Snapshot>, interfaces: Vec<String>) {
+    // Ctrl-C wakes the loop up so it can stop between updates
+    let (stop_tx, stop_rx) = mpsc::channel();
+    if let Err(err) = ctrlc::set_handler(move || {
+        let _ = stop_tx.send(());
+    }) {
+        eprintln!("Failed to install Ctrl-C handler: {err}");
+    }
+
+    let mut monitor = NetworkMonitor::new(Backend::Sysinfo);
+    monitor.set_allowlist(interfaces);
+    // Stop on Ctrl-C, or if the handler is gone
+    while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+        let mut stats = monitor.refresh(true, false);
+        stats.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(snapshot) = &metrics {
+            metrics::update(snapshot, &stats);
+        }
+        print_table(&stats);
+        if let Some(diagnostic) = monitor.diagnostic(stats.len()) {
+            eprintln!("{diagnostic}");
+        }
+    }
+}
+
+fn print_table(stats: &[NetworkStats]) {
+    let format = RateFormat::default();
+    let width = stats
+        .iter()
+        .map(|s| s.name.len())
+        .chain(["Interface".len()])
+        .max()
+        .unwrap_or_default();
+    println!(
+        "{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+        "Interface", "Download", "Upload", "Received", "Sent"
+    );
+    for s in stats {
+        println!(
+            "{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+            s.name,
+            format_bytes(s.download_speed, format),
+            format_bytes(s.upload_speed, format),
+            format_total_bytes(s.bytes_received, format.number),
+            format_total_bytes(s.bytes_transmitted, format.number)
+        );
+    }
+    println!();
+}
@@ -0,0 +1,118 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// How far back sparklines reach by default, and the range allowed in settings
+pub const DEFAULT_SPARKLINE_SPAN: Duration = Duration::from_secs(60);
+pub const MIN_SPARKLINE_SPAN: Duration = Duration::from_secs(10);
+pub const MAX_SPARKLINE_SPAN: Duration = Duration::from_secs(10 * 60);
+
+// Number of samples averaged for the sparkline trend overlay
+pub const AVERAGE_WINDOW: usize = 10;
+
+// How far back the history graph window reaches
+pub const PLOT_SPAN: Duration = Duration::from_secs(5 * 60);
+
+// Timestamped (download, upload) samples covering a span of time, for
+// sparklines and graphs. Trimming by age rather than sample count keeps the
+// time covered the same when the refresh interval changes.
+#[derive(Debug, Default)]
+pub struct TimedHistory {
+    samples: VecDeque<(Instant, f64, f64)>,
+}
+
+impl TimedHistory {
+    // Add a sample and drop those older than `span`
+    pub fn push(&mut self, now: Instant, download_speed: f64, upload_speed: f64, span: Duration) {
+        self.samples.push_back((now, download_speed, upload_speed));
+        while let Some((time, _, _)) = self.samples.front()
+            && now.duration_since(*time) > span
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    // Mean download speed over the last `window`, like a load average. None
+    // until the history reaches back that far.
+    pub fn mean_download(&self, now: Instant, window: Duration) -> Option<f64> {
+        let (oldest, _, _) = self.samples.front()?;
+        if now.duration_since(*oldest) < window {
+            return None;
+        }
+        let (sum, count) = self
+            .samples
+            .iter()
+            .filter(|(time, _, _)| now.duration_since(*time) <= window)
+            .fold((0.0, 0), |(sum, count), (_, down, _)| {
+                (sum + down, count + 1)
+            });
+        (count > 0).then(|| sum / count as f64)
+    }
+
+    // (seconds before `now`, download, upload), oldest first
+    pub fn points(&self, now: Instant) -> impl Iterator<Item = (f64, f64, f64)> + '_ {
+        self.samples
+            .iter()
+            .map(move |(time, down, up)| (now.duration_since(*time).as_secs_f64(), *down, *up))
+    }
+}
+
+// Upper edges of the speed ranges in a distribution, in bytes per second.
+// Each range is ten times the previous one; the last range is open-ended.
+pub const DISTRIBUTION_EDGES: [f64; 6] = [
+    1024.0,
+    10.0 * 1024.0,
+    100.0 * 1024.0,
+    1024.0 * 1024.0,
+    10.0 * 1024.0 * 1024.0,
+    100.0 * 1024.0 * 1024.0,
+];
+
+// How many samples fell into each speed range over the session
+#[derive(Debug, Default)]
+pub struct SpeedDistribution {
+    counts: [u64; DISTRIBUTION_EDGES.len() + 1],
+}
+
+impl SpeedDistribution {
+    pub fn record(&mut self, speed: f64) {
+        let bucket = DISTRIBUTION_EDGES
+            .iter()
+            .position(|edge| speed < *edge)
+            .unwrap_or(DISTRIBUTION_EDGES.len());
+        self.counts[bucket] += 1;
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.counts = Default::default();
+    }
+}
+
+// Trailing simple moving average: each output is the mean of up to `window`
+// samples ending at the same position. Runs in O(n) using a running sum.
+pub fn moving_average(values: &[f64], window: usize) -> Vec<f64> {
+    let window = window.max(1);
+    let mut sum = 0.0;
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            sum += value;
+            if i >= window {
+                sum -= values[i - window];
+            }
+            sum / (i + 1).min(window) as f64
+        })
+        .collect()
+}
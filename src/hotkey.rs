@@ -0,0 +1,67 @@
+// System-wide hotkey that shows or hides the window, even when it isn't focused.
+//
+// Platform caveats: on Linux this works through X11 only; under Wayland the
+// compositor doesn't let apps grab global keys, so the hotkey is ignored (or
+// only fires while an XWayland window has focus). On macOS the combo is
+// registered through Carbon, which needs no extra permission, but some combos
+// are reserved by the system and fail to register.
+//
+// Threading: key events arrive on the platform event loop (a background X11
+// thread on Linux). Like the tray menu, the handler only sends viewport
+// commands and requests a repaint, so it works while the window is minimized.
+use eframe::egui;
+use global_hotkey::hotkey::HotKey;
+use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+pub struct Hotkey {
+    manager: GlobalHotKeyManager,
+    registered: Option<HotKey>,
+}
+
+impl Hotkey {
+    pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+        let manager = GlobalHotKeyManager::new().map_err(|e| e.to_string())?;
+
+        let ctx = ctx.clone();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            // Only one hotkey is ever registered, so any press is ours
+            if event.state() != HotKeyState::Pressed {
+                return;
+            }
+            // Minimizing rather than hiding, for the same reason as the tray menu
+            let minimized = ctx.input_for(egui::ViewportId::ROOT, |i| {
+                i.viewport().minimized.unwrap_or(false)
+            });
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(!minimized));
+            if minimized {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            }
+            ctx.request_repaint();
+        }));
+
+        Ok(Self {
+            manager,
+            registered: None,
+        })
+    }
+
+    // Replace the registered combo, e.g. "Ctrl+Alt+Shift+S". An empty combo turns
+    // the hotkey off. On failure the previous combo is no longer registered.
+    pub fn set(&mut self, combo: &str) -> Result<(), String> {
+        if let Some(old) = self.registered.take() {
+            let _ = self.manager.unregister(old);
+        }
+        if combo.trim().is_empty() {
+            return Ok(());
+        }
+        let hotkey: HotKey = combo
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid hotkey \"{combo}\": {e}"))?;
+        self.manager
+            .register(hotkey)
+            .map_err(|e| format!("Couldn't register \"{combo}\": {e}"))?;
+        self.registered = Some(hotkey);
+        Ok(())
+    }
+}
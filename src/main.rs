@@ -1,341 +1,3800 @@
 #![cfg_attr(target_os = "windows", windows_subsystem = "windows")]
 
+mod cli;
+mod export;
+mod headless;
+mod history;
+mod hotkey;
+mod metrics;
+#[cfg(target_os = "linux")]
+mod network_linux;
+#[cfg(target_os = "macos")]
+mod network_macos;
 mod network_monitor;
+#[cfg(target_os = "windows")]
+mod network_windows;
+mod quota;
+mod settings;
+mod subnet;
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+mod tray;
 
 use eframe::egui;
-use network_monitor::{NetworkMonitor, NetworkStats, format_bytes, format_total_bytes};
+use history::{SpeedDistribution, TimedHistory};
+use network_monitor::{
+    Backend, InterfaceKind, NetworkMonitor, NetworkStats, NumberFormat, RateFormat, SpeedUnit,
+    TimeBase, UnitBase, classify_interface, format_bytes, format_bytes_with_unit,
+    format_exact_bytes, format_total_bytes, sticky_rate_unit,
+};
+use serde::{Deserialize, Serialize};
+use settings::MemoryStorage;
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
+use subnet::Subnet;
 const STORAGE_KEY: &str = "speedy.sort_mode";
+const SORT_DESCENDING_KEY: &str = "speedy.sort_descending";
+const ALWAYS_ON_TOP_KEY: &str = "speedy.always_on_top";
+const ACTIVE_GRACE_KEY: &str = "speedy.active_grace_secs";
+const INTERVAL_KEY: &str = "speedy.update_interval_ms";
+const ADAPTIVE_KEY: &str = "speedy.adaptive_interval";
+const ADAPTIVE_FAST_KEY: &str = "speedy.adaptive_fast_ms";
+const ADAPTIVE_SLOW_KEY: &str = "speedy.adaptive_slow_ms";
+const ADAPTIVE_THRESHOLD_KEY: &str = "speedy.adaptive_threshold";
+const SMOOTHING_KEY: &str = "speedy.smoothing";
+const SMOOTHING_ALPHA_KEY: &str = "speedy.smoothing_alpha";
+const SHOW_VIRTUAL_KEY: &str = "speedy.show_virtual";
+const SHOW_LOOPBACK_KEY: &str = "speedy.show_loopback";
+const DEFAULT_FIRST_KEY: &str = "speedy.default_first";
+const ACTIVE_FIRST_KEY: &str = "speedy.active_first";
+const MIN_SPEED_KEY: &str = "speedy.min_speed";
+const COLLAPSED_KEY: &str = "speedy.collapsed_interfaces";
+const ACTIVE_ONLY_KEY: &str = "speedy.active_only";
+const ACTIVE_ONLY_IDLE_KEY: &str = "speedy.active_only_idle_secs";
+const SUBNET_FILTER_KEY: &str = "speedy.subnet_filter";
+const SEARCH_QUERY_KEY: &str = "speedy.search_query";
+const HIDDEN_INTERFACES_KEY: &str = "speedy.hidden_interfaces";
+const SUM_SELECTED_KEY: &str = "speedy.sum_selected";
+const SELECTED_INTERFACES_KEY: &str = "speedy.selected_interfaces";
+const LIST_SELECTED_KEY: &str = "speedy.list_selected";
+const ALERT_KEY: &str = "speedy.alert";
+const ALERT_THRESHOLD_KEY: &str = "speedy.alert_threshold";
+const UPLOAD_WATCH_KEY: &str = "speedy.upload_watch";
+const UPLOAD_WATCH_THRESHOLD_KEY: &str = "speedy.upload_watch_threshold";
+const UPLOAD_WATCH_SECS_KEY: &str = "speedy.upload_watch_secs";
+const UPLOAD_WATCH_NOTIFY_KEY: &str = "speedy.upload_watch_notify";
+const TIME_BASE_KEY: &str = "speedy.time_base";
+const PRECISION_KEY: &str = "speedy.precision";
+const UNIT_BASE_KEY: &str = "speedy.unit_base";
+const SPEED_UNIT_KEY: &str = "speedy.speed_unit";
+const SPARKLINE_AVERAGE_KEY: &str = "speedy.sparkline_average";
+const SPARKLINE_SPAN_KEY: &str = "speedy.sparkline_span_secs";
+const SHOW_DELTAS_KEY: &str = "speedy.show_deltas";
+const MONOSPACE_KEY: &str = "speedy.monospace_speeds";
+const HOTKEY_KEY: &str = "speedy.hotkey";
+const QUOTA_GB_KEY: &str = "speedy.quota_gb";
+const QUOTA_START_DAY_KEY: &str = "speedy.quota_start_day";
+// Running quota usage; app state rather than a setting, so not exported
+const QUOTA_USED_KEY: &str = "speedy.quota_used";
+const QUOTA_CYCLE_KEY: &str = "speedy.quota_cycle_start";
+const BACKEND_KEY: &str = "speedy.backend";
+#[cfg(target_os = "windows")]
+const CONNECTION_FAMILY_KEY: &str = "speedy.connection_family";
+const GRID_LAYOUT_KEY: &str = "speedy.grid_layout";
+const FREEZE_ON_HOVER_KEY: &str = "speedy.freeze_on_hover";
+const UPLOAD_FIRST_KEY: &str = "speedy.upload_first";
+const HEATMAP_KEY: &str = "speedy.heatmap";
+const SUMMARY_KEY: &str = "speedy.show_summary";
+const TOTAL_GRAPH_KEY: &str = "speedy.show_total_graph";
+const COMPACT_KEY: &str = "speedy.compact";
+const PALETTE_KEY: &str = "speedy.palette";
+const THEME_KEY: &str = "speedy.theme";
+// Window geometry is machine-specific, so it is saved but not exported
+const WINDOW_X_KEY: &str = "speedy.window_x";
+const WINDOW_Y_KEY: &str = "speedy.window_y";
+const WINDOW_W_KEY: &str = "speedy.window_width";
+const WINDOW_H_KEY: &str = "speedy.window_height";
+const TOTAL_HIGHLIGHT_KEY: &str = "speedy.total_highlight";
+const TOTAL_WARN_KEY: &str = "speedy.total_warn_bytes";
+const TOTAL_HIGH_KEY: &str = "speedy.total_high_bytes";
+const MEDIUM_SPEED_KEY: &str = "speedy.medium_speed";
+const FAST_SPEED_KEY: &str = "speedy.fast_speed";
+
+// Refresh intervals offered in the control row
+const INTERVAL_CHOICES: &[Duration] = &[
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+];
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+const HEATMAP_CELL: f32 = 14.0;
+// Narrowest an interface card gets in the grid layout
+const MIN_CARD_W: f32 = 330.0;
+// Cards stretched across more columns than this get hard to scan
+const MAX_CARD_COLUMNS: usize = 3;
+const MIN_WINDOW_SIZE: egui::Vec2 = egui::vec2(450.0, 300.0);
+// Until the first frame measures the controls; see `fit_width_to_controls`
+const INITIAL_WINDOW_SIZE: egui::Vec2 = egui::vec2(640.0, 360.0);
+// Window size in compact mode: a single line of text
+const COMPACT_WINDOW_SIZE: egui::Vec2 = egui::vec2(300.0, 40.0);
 
 struct SpeedyApp {
     network_monitor: NetworkMonitor,
     network_stats: Vec<NetworkStats>,
+    rate_units: HashMap<String, (usize, usize)>, // interface -> (download, upload) unit index
+    histories: HashMap<String, TimedHistory>,    // sparklines, covering `sparkline_span`
+    sparkline_span: Duration,
+    plot_histories: HashMap<String, TimedHistory>, // longer history for the graph window
+    graph_interface: Option<String>,               // interface shown in the graph window
+    distributions: HashMap<String, SpeedDistribution>, // download speeds seen this session
+    distribution_interface: Option<String>,        // interface shown in the distribution window
+    detached: BTreeSet<String>,                    // interfaces popped out into their own window
+    marks: [Option<CounterSnapshot>; 2],           // "Mark A" and "Mark B"
+    total_history: TimedHistory,                   // combined speeds of all interfaces
+    peaks: HashMap<String, (f64, f64)>, // interface -> (download, upload) max since start/reset
+    last_traffic: HashMap<String, Instant>, // interface -> refresh that last saw traffic
+    sessions: HashMap<String, SessionTotals>,
+    quota: quota::Quota,
+    show_sparkline_average: bool,
+    show_deltas: bool, // bytes moved in the last refresh interval
+    monospace_speeds: bool,
+    show_heatmap: bool,
+    show_summary: bool,
+    show_total_graph: bool,
+    compact: bool, // single-line view of the busiest interface
+    palette: PaletteKind,
+    theme: egui::ThemePreference,
+    grid_layout: bool,
+    scroll_to_interface: Option<String>, // set by clicking a heatmap cell
+    scroll_anchor: Option<String>,       // topmost interface currently in view
+    frozen: HashMap<String, HeldStats>,  // interface -> stats captured when frozen
+    freeze_on_hover: bool,               // hold the numbers of the card under the pointer
+    upload_first: bool,                  // show upload before download everywhere
+    hover_snapshot: Option<HeldStats>,   // values shown by that card since the pointer arrived
+    // Optional emphasis of lifetime totals (off by default)
+    total_highlight: bool,
+    total_warn_bytes: u64,
+    total_high_bytes: u64,
+    medium_speed: f64, // speed color thresholds in bytes per second; medium < fast
+    fast_speed: f64,
+    start_time: Instant,
     last_update: Instant,
+    empty_refreshes: u32, // consecutive refreshes with no interface to show
     update_interval: Duration,
+    adaptive_interval: bool,
+    fast_interval: Duration,
+    slow_interval: Duration,
+    adaptive_threshold: f64, // aggregate bytes per second that counts as "busy"
+    adaptive_busy: bool,     // currently refreshing at the fast interval
+    paused: bool,
     always_on_top: bool,
     first_frame: bool,
+    window_pos: Option<egui::Pos2>,
+    window_size: Option<egui::Vec2>,
     sort_mode: SortMode,
+    sort_descending: bool,
+    rate_format: RateFormat,
     search_query: String,
+    regex_search: bool,
+    search_regex: Option<regex::Regex>, // last query that compiled, in regex mode
+    search_error: Option<String>,       // why the current query doesn't compile
+    subnet_filter: String,
+    show_virtual: bool,
+    show_loopback: bool,
+    default_first: bool, // keep the default-route interface on top when sorting by name
+    active_first: bool,  // list active interfaces above idle ones in every sort mode
+    min_speed: f64,      // combined bytes per second an interface needs to be listed; 0 = all
+    active_only: bool,
+    active_only_idle: Duration, // idle time after which "Active only" hides an interface
+    hidden_interfaces: BTreeSet<String>, // interface names unchecked in the Interfaces panel
+    sum_selected: bool, // Interfaces panel checkboxes pick interfaces to sum instead of hiding
+    selected_interfaces: BTreeSet<String>, // interface names checked in "sum" mode
+    list_selected: bool, // still list summed interfaces individually
+    collapsed: BTreeSet<String>, // interface cards collapsed to their header
+    show_settings: bool,
+    privacy_mode: bool,
+    storage_available: bool,
+    config_path: Option<PathBuf>, // config.json, if there's somewhere to keep it
+    written_config: Option<settings::Config>, // as last read from or written to config.json
+    settings_path: String,
+    settings_status: Option<String>,
+    status_message: Option<String>, // result of the last export
+    recorder: Option<export::Recorder>,
+    metrics: Option<metrics::Snapshot>, // shared with the metrics server, if enabled
+    // Desktop notification when a download speed rises above the threshold
+    alert_enabled: bool,
+    alert_threshold: f64,     // bytes per second
+    alerted: HashSet<String>, // interfaces currently above the threshold
+    // Flag interfaces whose upload stays above a threshold for a while
+    upload_watch: bool,
+    upload_watch_threshold: f64, // bytes per second
+    upload_watch_for: Duration,  // how long the upload has to stay above it
+    upload_watch_notify: bool,
+    upload_over_since: HashMap<String, Instant>, // interface -> when upload went above
+    upload_flagged: HashSet<String>,             // interfaces above it for long enough
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    tray: Option<tray::Tray>,
+    hotkey: Option<hotkey::Hotkey>,
+    hotkey_combo: String,         // as entered in Settings; empty = no hotkey
+    hotkey_error: Option<String>, // why the combo couldn't be registered
 }
 
 impl Default for SpeedyApp {
     fn default() -> Self {
         Self {
-            network_monitor: NetworkMonitor::new(),
+            network_monitor: NetworkMonitor::new(Backend::Sysinfo),
             network_stats: Vec::new(),
+            rate_units: HashMap::new(),
+            histories: HashMap::new(),
+            sparkline_span: history::DEFAULT_SPARKLINE_SPAN,
+            plot_histories: HashMap::new(),
+            total_history: TimedHistory::default(),
+            graph_interface: None,
+            distributions: HashMap::new(),
+            distribution_interface: None,
+            detached: BTreeSet::new(),
+            marks: [None, None],
+            quota: quota::Quota::default(),
+            peaks: HashMap::new(),
+            last_traffic: HashMap::new(),
+            sessions: HashMap::new(),
+            show_sparkline_average: false,
+            show_deltas: false,
+            monospace_speeds: false,
+            show_heatmap: false,
+            show_summary: true,
+            show_total_graph: false,
+            compact: false,
+            palette: PaletteKind::Normal,
+            theme: egui::ThemePreference::Dark,
+            grid_layout: false,
+            scroll_to_interface: None,
+            scroll_anchor: None,
+            frozen: HashMap::new(),
+            freeze_on_hover: false,
+            upload_first: false,
+            hover_snapshot: None,
+            total_highlight: false,
+            medium_speed: DEFAULT_MEDIUM_SPEED,
+            fast_speed: DEFAULT_FAST_SPEED,
+            total_warn_bytes: GB as u64,
+            total_high_bytes: (10.0 * GB) as u64,
+            start_time: Instant::now(),
             last_update: Instant::now(),
+            empty_refreshes: 0,
             update_interval: Duration::from_secs(1),
+            adaptive_interval: false,
+            fast_interval: Duration::from_millis(250),
+            slow_interval: Duration::from_secs(2),
+            adaptive_threshold: 100.0 * 1024.0,
+            adaptive_busy: false,
+            paused: false,
             always_on_top: true,
             first_frame: true,
+            window_pos: None,
+            window_size: None,
             sort_mode: SortMode::Name,
+            sort_descending: SortMode::Name.default_descending(),
+            rate_format: RateFormat::default(),
             search_query: String::new(),
+            regex_search: false,
+            search_regex: None,
+            search_error: None,
+            subnet_filter: String::new(),
+            show_virtual: true,
+            show_loopback: false,
+            default_first: false,
+            active_first: false,
+            min_speed: 0.0,
+            active_only: false,
+            active_only_idle: Duration::from_secs(60),
+            hidden_interfaces: BTreeSet::new(),
+            sum_selected: false,
+            selected_interfaces: BTreeSet::new(),
+            list_selected: true,
+            collapsed: BTreeSet::new(),
+            show_settings: false,
+            privacy_mode: false,
+            storage_available: false,
+            config_path: None,
+            written_config: None,
+            settings_path: settings::default_export_path("speedy-settings.json")
+                .display()
+                .to_string(),
+            settings_status: None,
+            status_message: None,
+            recorder: None,
+            metrics: None,
+            alert_enabled: false,
+            alert_threshold: 50.0 * 1024.0 * 1024.0,
+            alerted: HashSet::new(),
+            upload_watch: false,
+            upload_watch_threshold: 5.0 * 1024.0 * 1024.0,
+            upload_watch_for: Duration::from_secs(60),
+            upload_watch_notify: true,
+            upload_over_since: HashMap::new(),
+            upload_flagged: HashSet::new(),
+            #[cfg(any(target_os = "windows", target_os = "macos"))]
+            tray: None,
+            hotkey: None,
+            // Opt-in: any default combo could clash with another app's shortcut
+            hotkey_combo: String::new(),
+            hotkey_error: None,
         }
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PaletteKind {
+    Normal,
+    Colorblind,
+}
+
+// Colors for the download/upload distinction and the speed thresholds
+struct Palette {
+    download: egui::Color32,
+    upload: egui::Color32,
+    fast: egui::Color32,   // above the fast threshold
+    medium: egui::Color32, // above the medium threshold
+    idle: egui::Color32,
+    speed_color_names: [&'static str; 3], // fast, medium, idle, for tooltips
+}
+
+// Default speed color thresholds, in bytes per second
+const DEFAULT_FAST_SPEED: f64 = 1024.0 * 1024.0;
+const DEFAULT_MEDIUM_SPEED: f64 = 1024.0;
+
+const NORMAL_PALETTE: Palette = Palette {
+    download: egui::Color32::from_rgb(20, 100, 200),
+    upload: egui::Color32::from_rgb(200, 100, 20),
+    fast: egui::Color32::from_rgb(0, 200, 0),
+    medium: egui::Color32::from_rgb(200, 150, 0),
+    idle: egui::Color32::from_rgb(80, 80, 80),
+    speed_color_names: ["green", "amber", "gray"],
+};
+
+// Blue/yellow from the Okabe-Ito set, which stays distinct for the common
+// forms of color blindness; the speed colors also differ in brightness
+const COLORBLIND_PALETTE: Palette = Palette {
+    download: egui::Color32::from_rgb(0, 114, 178),
+    upload: egui::Color32::from_rgb(220, 180, 0),
+    fast: egui::Color32::from_rgb(86, 180, 233),
+    medium: egui::Color32::from_rgb(230, 159, 0),
+    idle: egui::Color32::from_rgb(80, 80, 80),
+    speed_color_names: ["blue", "orange", "gray"],
+};
+
+impl Palette {
+    // Pick a color for a speed value (bytes per second). Thresholds are on the
+    // underlying byte rate, so colors don't change with the display unit.
+    fn speed_color(&self, value: f64, medium_speed: f64, fast_speed: f64) -> egui::Color32 {
+        if value > fast_speed {
+            self.fast
+        } else if value > medium_speed {
+            self.medium
+        } else {
+            self.idle
+        }
+    }
+}
+
+// Serialized by variant name, the same spelling as in storage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum SortMode {
     Name,
     Download,
+    Upload,
+    Total,
+}
+
+impl SortMode {
+    // Direction a mode starts in: names A-Z, speeds and totals largest first
+    fn default_descending(self) -> bool {
+        self != SortMode::Name
+    }
 }
 
 impl eframe::App for SpeedyApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // Apply always-on-top on first frame (since builder settings don't work reliably)
-        if self.first_frame && self.always_on_top {
-            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
-                egui::WindowLevel::AlwaysOnTop,
-            ));
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Apply the restored window level on first frame (since builder settings
+        // don't work reliably)
+        if self.first_frame {
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(self.window_level()));
+            self.restore_window_geometry(ctx);
+            if self.compact {
+                self.apply_compact_size(ctx);
+            } else if self.window_size.is_none() {
+                Self::fit_width_to_controls(ctx);
+            }
             self.first_frame = false;
         }
+        // Track the geometry so it can be saved on exit
+        ctx.input(|i| {
+            let viewport = i.viewport();
+            if let Some(rect) = viewport.outer_rect {
+                self.window_pos = Some(rect.min);
+            }
+            // The compact size is fixed; remember the full-view size instead
+            if let Some(rect) = viewport.inner_rect
+                && !self.compact
+            {
+                self.window_size = Some(rect.size());
+            }
+        });
 
         // Update network stats periodically
-        if self.last_update.elapsed() >= self.update_interval {
-            self.network_stats = self.network_monitor.refresh();
-            self.last_update = Instant::now();
+        let interval = self.effective_interval();
+        if !self.paused && self.last_update.elapsed() >= interval {
+            self.refresh_stats();
+        }
+
+        // Toggle privacy mode from the keyboard
+        let privacy_shortcut = egui::KeyboardShortcut::new(
+            egui::Modifiers::COMMAND | egui::Modifiers::SHIFT,
+            egui::Key::P,
+        );
+        if ctx.input_mut(|i| i.consume_shortcut(&privacy_shortcut)) {
+            self.privacy_mode = !self.privacy_mode;
         }
 
         // Request repaint to keep updating
-        ctx.request_repaint_after(self.update_interval);
+        ctx.request_repaint_after(interval);
+
+        if self.compact {
+            egui::CentralPanel::default().show(ctx, |ui| self.show_compact(ui));
+            return;
+        }
+
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| self.show_status_bar(ui, interval));
+
+        if self.show_heatmap {
+            egui::SidePanel::left("heatmap")
+                .resizable(false)
+                .exact_width(HEATMAP_CELL + 8.0)
+                .show(ctx, |ui| self.show_heatmap_strip(ui));
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            // Busiest interface badge (by combined speed, over the visible set)
+            let badge = match self.busiest_interface() {
+                Some(stats) => format!(
+                    "▶ {} {}",
+                    self.display_name(stats),
+                    format_bytes(stats.download_speed + stats.upload_speed, self.rate_format)
+                ),
+                None => "all idle".to_string(),
+            };
+            ui.label(egui::RichText::new(badge).strong().size(18.0));
+
+            ui.separator();
+
             // Controls
-            ui.horizontal(|ui| {
+            let sort_mode = self.sort_mode;
+            let sort_descending = self.sort_descending;
+            let search_query = self.search_query.clone();
+            ui.horizontal_wrapped(|ui| {
+                if ui
+                    .button("⊟ Compact")
+                    .on_hover_text("Show only the busiest interface on one line")
+                    .clicked()
+                {
+                    self.compact = true;
+                    self.apply_compact_size(ctx);
+                }
                 ui.separator();
                 ui.label("Search:");
-                ui.add(
+                let response = ui.add(
                     egui::TextEdit::singleline(&mut self.search_query)
                         .hint_text("Filter by name")
                         .desired_width(80.0), // ~10 ASCII chars
                 );
+                let regex_toggled = ui
+                    .toggle_value(&mut self.regex_search, "re")
+                    .on_hover_text("Treat the search as a regular expression")
+                    .changed();
+                if response.changed() || regex_toggled {
+                    self.compile_search_regex();
+                }
+                if let Some(err) = &self.search_error {
+                    // Keep filtering with the last valid pattern; flag the input
+                    ui.painter().rect_stroke(
+                        response.rect,
+                        2.0,
+                        egui::Stroke::new(1.0, egui::Color32::RED),
+                        egui::StrokeKind::Outside,
+                    );
+                    response.on_hover_text(err);
+                }
+                ui.label("Subnet:");
+                let subnet_error = self.parsed_subnet_filter().err();
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.subnet_filter)
+                        .hint_text("e.g. 10.0.0.0/8")
+                        .desired_width(100.0),
+                );
+                if let Some(err) = subnet_error {
+                    // Invalid input doesn't filter; flag it instead
+                    ui.painter().rect_stroke(
+                        response.rect,
+                        2.0,
+                        egui::Stroke::new(1.0, egui::Color32::RED),
+                        egui::StrokeKind::Outside,
+                    );
+                    response.on_hover_text(err);
+                }
+                ui.label("Min speed:");
+                let rate_format = self.rate_format;
+                ui.add(
+                    egui::Slider::new(&mut self.min_speed, 0.0..=1e9)
+                        .logarithmic(true)
+                        .smallest_positive(1024.0)
+                        .custom_formatter(move |value, _| {
+                            if value > 0.0 {
+                                format_bytes(value, rate_format)
+                            } else {
+                                "off".to_string()
+                            }
+                        }),
+                )
+                .on_hover_text("Hide interfaces with less combined traffic than this");
                 ui.separator();
                 ui.label("Sort:");
                 ui.selectable_value(&mut self.sort_mode, SortMode::Name, "Name");
                 ui.selectable_value(&mut self.sort_mode, SortMode::Download, "Download");
+                ui.selectable_value(&mut self.sort_mode, SortMode::Upload, "Upload");
+                ui.selectable_value(&mut self.sort_mode, SortMode::Total, "Total");
+                if self.sort_mode != sort_mode {
+                    self.sort_descending = self.sort_mode.default_descending();
+                }
+                let (arrow, hover) = if self.sort_descending {
+                    ("⬇", "Descending (click for ascending)")
+                } else {
+                    ("⬆", "Ascending (click for descending)")
+                };
+                if ui.small_button(arrow).on_hover_text(hover).clicked() {
+                    self.sort_descending = !self.sort_descending;
+                }
+                ui.add_enabled(
+                    self.sort_mode == SortMode::Name,
+                    egui::Checkbox::new(&mut self.default_first, "Default first"),
+                )
+                .on_hover_text("Keep the interface with the default route at the top")
+                .on_disabled_hover_text("Only applies when sorting by name");
+                ui.checkbox(&mut self.active_first, "Active first")
+                    .on_hover_text(
+                        "List active interfaces above idle ones, each group in sort order",
+                    );
+                ui.separator();
+                ui.label("Unit:");
+                let rate_format = self.rate_format;
+                ui.selectable_value(&mut self.rate_format.unit, SpeedUnit::Bytes, "B/s");
+                ui.selectable_value(&mut self.rate_format.unit, SpeedUnit::Bits, "bps");
+                ui.selectable_value(&mut self.rate_format.number.base, UnitBase::Binary, "1024")
+                    .on_hover_text("Binary units: 1 KB = 1024 bytes");
+                ui.selectable_value(&mut self.rate_format.number.base, UnitBase::Si, "1000")
+                    .on_hover_text("SI units, as ISPs quote speeds: 1 KB = 1000 bytes");
+                if self.rate_format != rate_format {
+                    self.update_rate_units();
+                }
+                ui.separator();
+                ui.label("Colors:");
+                ui.selectable_value(&mut self.palette, PaletteKind::Normal, "Normal");
+                ui.selectable_value(&mut self.palette, PaletteKind::Colorblind, "Colorblind");
+                ui.separator();
+                ui.label("Theme:");
+                let theme = self.theme;
+                ui.selectable_value(&mut self.theme, egui::ThemePreference::System, "System");
+                ui.selectable_value(&mut self.theme, egui::ThemePreference::Light, "Light");
+                ui.selectable_value(&mut self.theme, egui::ThemePreference::Dark, "Dark");
+                if self.theme != theme {
+                    // "System" follows the OS setting where it can be read, else dark
+                    ctx.set_theme(self.theme);
+                }
+                ui.separator();
+                let pause_label = if self.paused {
+                    "▶ Resume"
+                } else {
+                    "⏸ Pause"
+                };
+                if ui.button(pause_label).clicked() {
+                    self.paused = !self.paused;
+                    if !self.paused {
+                        // Measure from now on rather than across the pause
+                        self.network_monitor.rebaseline();
+                        self.last_update = Instant::now();
+                    }
+                }
+                let mut recording = self.recorder.is_some();
+                if ui
+                    .toggle_value(&mut recording, "⏺ Record")
+                    .on_hover_text("Append stats to a JSON Lines file on every refresh")
+                    .changed()
+                {
+                    self.toggle_recording();
+                }
+                if ui.button("Export CSV").clicked() {
+                    self.export_csv();
+                }
+                if ui
+                    .button("📋 Copy")
+                    .on_hover_text("Copy the listed interfaces as text")
+                    .clicked()
+                {
+                    ctx.copy_text(self.stats_summary());
+                    self.status_message = Some("Copied stats to the clipboard".to_string());
+                }
+                if ui.button("Reset peaks").clicked() {
+                    self.peaks.clear();
+                }
+                if ui
+                    .button("Reset session")
+                    .on_hover_text("Start counting session totals and averages from now")
+                    .clicked()
+                {
+                    for session in self.sessions.values_mut() {
+                        session.received = 0;
+                        session.transmitted = 0;
+                        session.started = Instant::now();
+                    }
+                }
+                for (mark, label) in self.marks.iter_mut().zip(["Mark A", "Mark B"]) {
+                    if ui
+                        .button(label)
+                        .on_hover_text("Remember every interface's totals now, to compare A and B")
+                        .clicked()
+                    {
+                        *mark = Some(CounterSnapshot {
+                            taken: Instant::now(),
+                            stats: self.network_stats.clone(),
+                        });
+                    }
+                }
+                ui.separator();
+                ui.checkbox(&mut self.alert_enabled, "Alert above")
+                    .on_hover_text("Desktop notification when a download speed exceeds this");
+                let mut mb = self.alert_threshold / (1024.0 * 1024.0);
+                if ui
+                    .add_enabled(
+                        self.alert_enabled,
+                        egui::DragValue::new(&mut mb)
+                            .range(0.1..=f64::MAX)
+                            .speed(0.5)
+                            .suffix(" MB/s"),
+                    )
+                    .changed()
+                {
+                    self.alert_threshold = mb * 1024.0 * 1024.0;
+                }
                 ui.separator();
                 if ui
                     .checkbox(&mut self.always_on_top, "Always on top")
                     .changed()
                 {
                     // Try to update always-on-top behavior
-                    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
-                        if self.always_on_top {
-                            egui::WindowLevel::AlwaysOnTop
-                        } else {
-                            egui::WindowLevel::Normal
-                        },
+                    ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(self.window_level()));
+                }
+                ui.separator();
+                ui.label("Interval:");
+                ui.add_enabled_ui(!self.adaptive_interval, |ui| {
+                    egui::ComboBox::from_id_salt("update_interval")
+                        .selected_text(format!("{}s", self.update_interval.as_secs_f64()))
+                        .width(50.0)
+                        .show_ui(ui, |ui| {
+                            for interval in INTERVAL_CHOICES {
+                                ui.selectable_value(
+                                    &mut self.update_interval,
+                                    *interval,
+                                    format!("{}s", interval.as_secs_f64()),
+                                );
+                            }
+                        });
+                })
+                .response
+                .on_disabled_hover_text("Adaptive refresh is enabled in Settings");
+                ui.separator();
+                ui.checkbox(&mut self.show_virtual, "Show virtual interfaces");
+                ui.checkbox(&mut self.show_loopback, "Show loopback")
+                    .on_hover_text("Include local traffic, e.g. to servers on this machine");
+                ui.checkbox(&mut self.active_only, "Active only")
+                    .on_hover_text(format!(
+                        "Hide interfaces without traffic in the last {} s (change in Settings)",
+                        self.active_only_idle.as_secs()
                     ));
+                ui.checkbox(&mut self.show_summary, "Show combined total");
+                ui.checkbox(&mut self.show_total_graph, "Total graph")
+                    .on_hover_text("Combined throughput of all interfaces over the last 5 minutes");
+                let mut smoothing = self.network_monitor.smoothing();
+                if ui
+                    .checkbox(&mut smoothing, "Smooth")
+                    .on_hover_text("Show a moving average of the speeds (strength in Settings)")
+                    .changed()
+                {
+                    self.network_monitor.set_smoothing(smoothing);
                 }
                 ui.separator();
+                ui.toggle_value(&mut self.privacy_mode, "🔒 Privacy")
+                    .on_hover_text(format!(
+                        "Hide interface names and addresses ({})",
+                        ctx.format_shortcut(&privacy_shortcut)
+                    ));
+                ui.separator();
+                ui.toggle_value(&mut self.show_settings, "⚙ Settings");
+                ui.separator();
                 ui.label(format!("Total interfaces: {}", self.network_stats.len()));
+                if !self.storage_available {
+                    ui.separator();
+                    ui.label(egui::RichText::new("⚠ Settings won't be saved").weak())
+                        .on_hover_text(
+                            "No settings storage is available. Start Speedy with \
+                             --data-dir <DIR> to store settings in a folder of your choice.",
+                        );
+                }
             });
 
+            if let Some(status) = &self.status_message {
+                ui.label(egui::RichText::new(status).small().weak());
+            }
+
+            self.show_interface_toggles(ui);
+
+            // Keep the interface at the top of the view in place when the order changes
+            if self.sort_mode != sort_mode
+                || self.sort_descending != sort_descending
+                || self.search_query != search_query
+            {
+                self.scroll_to_interface = self.scroll_anchor.clone();
+            }
+
             ui.separator();
 
             // Show network interfaces
             if self.network_stats.is_empty() {
-                ui.label("Scanning for network interfaces...");
+                self.show_empty_state(ui);
             } else {
                 self.show_network_interfaces(ui);
             }
         });
+
+        self.show_settings_window(ctx, frame);
+        self.show_graph_window(ctx);
+        self.show_distribution_window(ctx);
+        self.show_comparison_window(ctx);
+        self.show_detached_windows(ctx);
+    }
+
+    // Print what each interface moved this session on the way out
+    #[cfg(not(target_os = "windows"))]
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        println!("{}", self.session_summary());
+    }
+
+    #[cfg(target_os = "windows")]
+    fn on_exit(&mut self) {
+        println!("{}", self.session_summary());
     }
 
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        let s = match self.sort_mode {
-            SortMode::Name => "Name",
-            SortMode::Download => "Download",
-        };
-        storage.set_string(STORAGE_KEY, s.to_string());
+        self.save_settings(storage);
+        // Mirror the settings to config.json, but only once they differ from
+        // what the file last held, so hand edits aren't overwritten by autosaves
+        let mut current = MemoryStorage::default();
+        self.save_settings(&mut current);
+        let config = settings::Config::from_storage(&current);
+        if self.written_config.as_ref() != Some(&config)
+            && let Some(path) = &self.config_path
+        {
+            match settings::write_config(&config, path) {
+                Ok(()) => self.written_config = Some(config),
+                Err(err) => eprintln!("{err}"),
+            }
+        }
+        if let Some(pos) = self.window_pos {
+            storage.set_string(WINDOW_X_KEY, pos.x.to_string());
+            storage.set_string(WINDOW_Y_KEY, pos.y.to_string());
+        }
+        if let Some(size) = self.window_size {
+            storage.set_string(WINDOW_W_KEY, size.x.to_string());
+            storage.set_string(WINDOW_H_KEY, size.y.to_string());
+        }
+        storage.set_string(QUOTA_USED_KEY, self.quota.used.to_string());
+        if let Some(cycle_start) = self.quota.cycle_start {
+            storage.set_string(QUOTA_CYCLE_KEY, cycle_start.to_string());
+        }
     }
 }
 
 impl SpeedyApp {
-    fn show_network_interfaces(&self, ui: &mut egui::Ui) {
-        use egui::{Color32, RichText};
-
-        // helper to pick color for a speed value
-        let speed_color = |value: f64| -> Color32 {
-            if value > 1024.0 * 1024.0 {
-                Color32::from_rgb(0, 200, 0)
-            } else if value > 1024.0 {
-                Color32::from_rgb(200, 150, 0)
-            } else {
-                Color32::from_rgb(80, 80, 80)
-            }
+    // Take a new sample and update everything derived from it
+    fn refresh_stats(&mut self) {
+        self.network_stats = self
+            .network_monitor
+            .refresh(self.show_virtual, self.show_loopback);
+        self.last_update = Instant::now();
+        self.empty_refreshes = if self.network_stats.is_empty() {
+            self.empty_refreshes.saturating_add(1)
+        } else {
+            0
         };
+        self.update_adaptive_state();
+        self.update_rate_units();
+        self.update_histories();
+        self.update_peaks();
+        self.update_activity();
+        self.update_sessions();
+        self.update_quota();
+        self.record_stats();
+        self.check_speed_alerts();
+        self.check_sustained_uploads();
+        self.update_tray_tooltip();
+        if let Some(snapshot) = &self.metrics {
+            metrics::update(snapshot, &self.network_stats);
+        }
+    }
 
-        egui::ScrollArea::vertical().show(ui, |ui| {
-            // Sort interfaces according to user's choice. We create a vector
-            // of (index, &NetworkStats) so we can use the original index as
-            // a stable tiebreaker.
-            let mut indexed: Vec<(usize, &network_monitor::NetworkStats)> =
-                self.network_stats.iter().enumerate().collect();
-
-            // Apply search filter (case-insensitive) before sorting
-            let query = self.search_query.to_lowercase();
-            if !query.is_empty() {
-                indexed.retain(|(_i, s)| s.name.to_lowercase().contains(&query));
-            }
+    // Shown instead of the interface list while there's nothing to list. The
+    // first few empty refreshes are normal at startup; after that, explain.
+    fn show_empty_state(&mut self, ui: &mut egui::Ui) {
+        const EMPTY_REFRESHES_BEFORE_HELP: u32 = 3;
 
-            match self.sort_mode {
-                SortMode::Name => indexed.sort_by(|(i, a), (j, b)| {
-                    let ord = a.name.to_lowercase().cmp(&b.name.to_lowercase());
-                    if ord != Ordering::Equal {
-                        ord
-                    } else {
-                        i.cmp(j)
-                    }
-                }),
-                SortMode::Download => indexed.sort_by(|(i, a), (j, b)| {
-                    // Descending by download_speed
-                    match b
-                        .download_speed
-                        .partial_cmp(&a.download_speed)
-                        .unwrap_or(Ordering::Equal)
-                    {
-                        Ordering::Equal => i.cmp(j),
-                        other => other,
-                    }
-                }),
+        let diagnostic = self.network_monitor.diagnostic(0);
+        if diagnostic.is_none() && self.empty_refreshes < EMPTY_REFRESHES_BEFORE_HELP {
+            ui.label("Scanning for network interfaces...");
+            return;
+        }
+        ui.label(
+            egui::RichText::new(format!(
+                "⚠ {}",
+                diagnostic
+                    .as_deref()
+                    .unwrap_or("No network interfaces to show.")
+            ))
+            .color(egui::Color32::from_rgb(220, 160, 0)),
+        );
+        if self.empty_refreshes < EMPTY_REFRESHES_BEFORE_HELP {
+            return;
+        }
+        ui.add_space(6.0);
+        ui.label("Possible causes:");
+        for cause in [
+            "Network statistics can't be read here, e.g. inside a sandbox or container \
+             without access to them",
+            "Every interface is loopback or virtual, and those are hidden in Settings",
+            "The interfaces given with --interface or SPEEDY_INTERFACES don't exist",
+            "The system has no network adapters",
+        ] {
+            ui.label(format!("• {cause}"));
+        }
+        ui.add_space(6.0);
+        if ui
+            .button("⟳ Retry")
+            .on_hover_text("Look for interfaces again now")
+            .clicked()
+        {
+            self.refresh_stats();
+        }
+    }
+
+    // Refresh interval to use right now. In adaptive mode, refresh quickly while
+    // there is significant traffic and slowly when idle. Speeds stay correct
+    // either way since they are computed from the real elapsed time.
+    fn effective_interval(&self) -> Duration {
+        if !self.adaptive_interval {
+            return self.update_interval;
+        }
+        if self.adaptive_busy {
+            self.fast_interval
+        } else {
+            self.slow_interval
+        }
+    }
+
+    // Switch to the fast interval once the combined speed rises above the
+    // threshold, and back only once it falls well below it, so traffic
+    // hovering around the threshold doesn't flip the interval every refresh.
+    fn update_adaptive_state(&mut self) {
+        // Fraction of the threshold the speed has to drop under to count as idle again
+        const IDLE_FRACTION: f64 = 0.5;
+        let aggregate: f64 = self
+            .network_stats
+            .iter()
+            .map(|s| s.download_speed + s.upload_speed)
+            .sum();
+        if aggregate > self.adaptive_threshold {
+            self.adaptive_busy = true;
+        } else if aggregate < self.adaptive_threshold * IDLE_FRACTION {
+            self.adaptive_busy = false;
+        }
+    }
+
+    // Record the latest speeds, keyed by interface name so history survives
+    // reordering. Interfaces that disappear lose their history.
+    fn update_histories(&mut self) {
+        self.histories
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        self.plot_histories
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        self.distributions
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        let now = Instant::now();
+        let (download, upload) = self.network_stats.iter().fold((0.0, 0.0), |(down, up), s| {
+            (down + s.download_speed, up + s.upload_speed)
+        });
+        self.total_history
+            .push(now, download, upload, history::PLOT_SPAN);
+        for stats in &self.network_stats {
+            self.histories.entry(stats.name.clone()).or_default().push(
+                now,
+                stats.download_speed,
+                stats.upload_speed,
+                self.sparkline_span,
+            );
+            self.plot_histories
+                .entry(stats.name.clone())
+                .or_default()
+                .push(
+                    now,
+                    stats.download_speed,
+                    stats.upload_speed,
+                    history::PLOT_SPAN,
+                );
+            // Unsmoothed, so the spread of speeds isn't narrowed
+            self.distributions
+                .entry(stats.name.clone())
+                .or_default()
+                .record(stats.raw_download_speed);
+        }
+    }
+
+    // Remember when each interface last moved data, for its activity light
+    fn update_activity(&mut self) {
+        self.last_traffic
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        let now = Instant::now();
+        for stats in &self.network_stats {
+            if stats.raw_download_speed + stats.raw_upload_speed > 0.0 {
+                self.last_traffic.insert(stats.name.clone(), now);
             }
+        }
+    }
 
-            for (_idx, stats) in indexed {
-                ui.group(|ui| {
-                    ui.horizontal(|ui| {
-                        // Interface name
-                        ui.label(RichText::new(&stats.name).strong().size(16.0));
-
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            ui.label(format!(
-                                "Total: Down:{} Up:{}",
-                                format_total_bytes(stats.bytes_received),
-                                format_total_bytes(stats.bytes_transmitted)
-                            ));
-                        });
+    // Track the highest speeds seen per interface. Interfaces that disappear
+    // drop their peaks so a later reappearance starts fresh.
+    fn update_peaks(&mut self) {
+        self.peaks
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        for stats in &self.network_stats {
+            let peak = self.peaks.entry(stats.name.clone()).or_default();
+            peak.0 = peak.0.max(stats.download_speed);
+            peak.1 = peak.1.max(stats.upload_speed);
+        }
+    }
+
+    // Accumulate bytes moved since startup (or the last session reset). Adding
+    // up deltas rather than subtracting a fixed baseline keeps the total right
+    // across counter resets; interfaces seen later start from their first value.
+    fn update_sessions(&mut self) {
+        self.sessions
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        for stats in &self.network_stats {
+            let session =
+                self.sessions
+                    .entry(stats.name.clone())
+                    .or_insert_with(|| SessionTotals {
+                        received: 0,
+                        transmitted: 0,
+                        last_received: stats.bytes_received,
+                        last_transmitted: stats.bytes_transmitted,
+                        started: Instant::now(),
                     });
+            // A counter going backwards was reset; count nothing for this step
+            session.received += stats.bytes_received.saturating_sub(session.last_received);
+            session.transmitted += stats
+                .bytes_transmitted
+                .saturating_sub(session.last_transmitted);
+            session.last_received = stats.bytes_received;
+            session.last_transmitted = stats.bytes_transmitted;
+        }
+    }
 
-                    ui.separator();
+    // Count the last interval's traffic towards the monthly quota. Only the
+    // default-route interface is counted, as that's the metered connection;
+    // if none is detected, all listed non-virtual interfaces are.
+    fn update_quota(&mut self) {
+        if !self.quota.enabled() {
+            return;
+        }
+        // Loopback and virtual traffic never reaches the provider, whatever
+        // is shown; of the rest, only the default route counts if known
+        let physical: Vec<&NetworkStats> = self
+            .network_stats
+            .iter()
+            .filter(|s| {
+                !network_monitor::is_loopback_interface(&s.name)
+                    && !network_monitor::is_virtual_interface(&s.name)
+            })
+            .collect();
+        let has_default = physical.iter().any(|s| s.is_default);
+        let bytes = physical
+            .iter()
+            .filter(|s| s.is_default || !has_default)
+            .fold(0u64, |total, s| {
+                total.saturating_add(s.rx_delta).saturating_add(s.tx_delta)
+            });
+        self.quota.add(quota::Date::today(), bytes);
+    }
 
-                    // Speed display
-                    ui.horizontal(|ui| {
-                        // Download speed
-                        ui.group(|ui| {
-                            ui.vertical(|ui| {
-                                ui.label(
-                                    RichText::new("Download")
-                                        .color(Color32::from_rgb(20, 100, 200)),
-                                );
-                                let speed_text = format_bytes(stats.download_speed);
-                                let speed_color = speed_color(stats.download_speed);
-                                // Ensure a minimum width so values align between download/upload
-                                const SPEED_MIN_W: f32 = 110.0;
-                                const SPEED_H: f32 = 28.0;
-                                ui.add_sized(
-                                    [SPEED_MIN_W, SPEED_H],
-                                    egui::Label::new(
-                                        RichText::new(speed_text)
-                                            .color(speed_color)
-                                            .size(18.0)
-                                            .strong(),
-                                    ),
-                                );
-                            });
-                        });
+    // Keep a per-interface display unit that only changes once a speed clearly
+    // crosses a unit boundary.
+    fn update_rate_units(&mut self) {
+        let mut units = HashMap::new();
+        for stats in &self.network_stats {
+            let (down, up) = self
+                .rate_units
+                .get(&stats.name)
+                .copied()
+                .unwrap_or_default();
+            units.insert(
+                stats.name.clone(),
+                (
+                    sticky_rate_unit(stats.download_speed, down, self.rate_format),
+                    sticky_rate_unit(stats.upload_speed, up, self.rate_format),
+                ),
+            );
+        }
+        self.rate_units = units;
+    }
 
-                        ui.add_space(20.0);
+    fn save_settings(&self, storage: &mut dyn eframe::Storage) {
+        storage.set_string(
+            settings::VERSION_KEY,
+            settings::SETTINGS_VERSION.to_string(),
+        );
+        let s = match self.sort_mode {
+            SortMode::Name => "Name",
+            SortMode::Download => "Download",
+            SortMode::Upload => "Upload",
+            SortMode::Total => "Total",
+        };
+        storage.set_string(STORAGE_KEY, s.to_string());
+        storage.set_string(SORT_DESCENDING_KEY, self.sort_descending.to_string());
+        storage.set_string(ALWAYS_ON_TOP_KEY, self.always_on_top.to_string());
+        storage.set_string(
+            ACTIVE_GRACE_KEY,
+            self.network_monitor
+                .active_grace()
+                .as_secs_f64()
+                .to_string(),
+        );
+        storage.set_string(INTERVAL_KEY, self.update_interval.as_millis().to_string());
+        storage.set_string(ADAPTIVE_KEY, self.adaptive_interval.to_string());
+        storage.set_string(SMOOTHING_KEY, self.network_monitor.smoothing().to_string());
+        storage.set_string(
+            SMOOTHING_ALPHA_KEY,
+            self.network_monitor.smoothing_alpha().to_string(),
+        );
+        storage.set_string(
+            ADAPTIVE_FAST_KEY,
+            self.fast_interval.as_millis().to_string(),
+        );
+        storage.set_string(
+            ADAPTIVE_SLOW_KEY,
+            self.slow_interval.as_millis().to_string(),
+        );
+        storage.set_string(ADAPTIVE_THRESHOLD_KEY, self.adaptive_threshold.to_string());
+        storage.set_string(SUBNET_FILTER_KEY, self.subnet_filter.clone());
+        storage.set_string(SEARCH_QUERY_KEY, self.search_query.clone());
+        let hidden: Vec<&str> = self.hidden_interfaces.iter().map(String::as_str).collect();
+        storage.set_string(HIDDEN_INTERFACES_KEY, hidden.join(","));
+        storage.set_string(SUM_SELECTED_KEY, self.sum_selected.to_string());
+        let selected: Vec<&str> = self
+            .selected_interfaces
+            .iter()
+            .map(String::as_str)
+            .collect();
+        storage.set_string(SELECTED_INTERFACES_KEY, selected.join(","));
+        storage.set_string(LIST_SELECTED_KEY, self.list_selected.to_string());
+        let collapsed: Vec<&str> = self.collapsed.iter().map(String::as_str).collect();
+        storage.set_string(COLLAPSED_KEY, collapsed.join(","));
+        storage.set_string(ALERT_KEY, self.alert_enabled.to_string());
+        storage.set_string(ALERT_THRESHOLD_KEY, self.alert_threshold.to_string());
+        storage.set_string(UPLOAD_WATCH_KEY, self.upload_watch.to_string());
+        storage.set_string(
+            UPLOAD_WATCH_THRESHOLD_KEY,
+            self.upload_watch_threshold.to_string(),
+        );
+        storage.set_string(
+            UPLOAD_WATCH_SECS_KEY,
+            self.upload_watch_for.as_secs().to_string(),
+        );
+        storage.set_string(
+            UPLOAD_WATCH_NOTIFY_KEY,
+            self.upload_watch_notify.to_string(),
+        );
+        storage.set_string(SHOW_VIRTUAL_KEY, self.show_virtual.to_string());
+        storage.set_string(SHOW_LOOPBACK_KEY, self.show_loopback.to_string());
+        storage.set_string(DEFAULT_FIRST_KEY, self.default_first.to_string());
+        storage.set_string(ACTIVE_FIRST_KEY, self.active_first.to_string());
+        storage.set_string(MIN_SPEED_KEY, self.min_speed.to_string());
+        storage.set_string(ACTIVE_ONLY_KEY, self.active_only.to_string());
+        storage.set_string(
+            ACTIVE_ONLY_IDLE_KEY,
+            self.active_only_idle.as_secs().to_string(),
+        );
+        let time_base = match self.rate_format.time_base {
+            TimeBase::Second => "Second",
+            TimeBase::Minute => "Minute",
+            TimeBase::Hour => "Hour",
+        };
+        storage.set_string(TIME_BASE_KEY, time_base.to_string());
+        let precision = match self.rate_format.number.precision {
+            Some(decimals) => decimals.to_string(),
+            None => "auto".to_string(),
+        };
+        storage.set_string(PRECISION_KEY, precision);
+        let unit_base = match self.rate_format.number.base {
+            UnitBase::Binary => "Binary",
+            UnitBase::Si => "Si",
+        };
+        storage.set_string(UNIT_BASE_KEY, unit_base.to_string());
+        let speed_unit = match self.rate_format.unit {
+            SpeedUnit::Bytes => "Bytes",
+            SpeedUnit::Bits => "Bits",
+        };
+        storage.set_string(SPEED_UNIT_KEY, speed_unit.to_string());
+        storage.set_string(TOTAL_HIGHLIGHT_KEY, self.total_highlight.to_string());
+        storage.set_string(TOTAL_WARN_KEY, self.total_warn_bytes.to_string());
+        storage.set_string(TOTAL_HIGH_KEY, self.total_high_bytes.to_string());
+        storage.set_string(MEDIUM_SPEED_KEY, self.medium_speed.to_string());
+        storage.set_string(FAST_SPEED_KEY, self.fast_speed.to_string());
+        let backend = match self.network_monitor.backend() {
+            Backend::Sysinfo => "Sysinfo",
+            Backend::Sysfs => "Sysfs",
+            Backend::ProcNetDev => "ProcNetDev",
+        };
+        storage.set_string(BACKEND_KEY, backend.to_string());
+        #[cfg(target_os = "windows")]
+        {
+            use network_monitor::AddressFamily;
+            let family = match self.network_monitor.connection_family() {
+                AddressFamily::Both => "Both",
+                AddressFamily::V4 => "IPv4",
+                AddressFamily::V6 => "IPv6",
+            };
+            storage.set_string(CONNECTION_FAMILY_KEY, family.to_string());
+        }
+        storage.set_string(GRID_LAYOUT_KEY, self.grid_layout.to_string());
+        storage.set_string(FREEZE_ON_HOVER_KEY, self.freeze_on_hover.to_string());
+        storage.set_string(UPLOAD_FIRST_KEY, self.upload_first.to_string());
+        storage.set_string(HEATMAP_KEY, self.show_heatmap.to_string());
+        storage.set_string(SHOW_DELTAS_KEY, self.show_deltas.to_string());
+        storage.set_string(MONOSPACE_KEY, self.monospace_speeds.to_string());
+        storage.set_string(HOTKEY_KEY, self.hotkey_combo.clone());
+        storage.set_string(QUOTA_GB_KEY, self.quota.limit_gb.to_string());
+        storage.set_string(QUOTA_START_DAY_KEY, self.quota.start_day.to_string());
+        storage.set_string(SUMMARY_KEY, self.show_summary.to_string());
+        storage.set_string(TOTAL_GRAPH_KEY, self.show_total_graph.to_string());
+        storage.set_string(COMPACT_KEY, self.compact.to_string());
+        let palette = match self.palette {
+            PaletteKind::Normal => "Normal",
+            PaletteKind::Colorblind => "Colorblind",
+        };
+        storage.set_string(PALETTE_KEY, palette.to_string());
+        let theme = match self.theme {
+            egui::ThemePreference::System => "System",
+            egui::ThemePreference::Light => "Light",
+            egui::ThemePreference::Dark => "Dark",
+        };
+        storage.set_string(THEME_KEY, theme.to_string());
+        storage.set_string(
+            SPARKLINE_SPAN_KEY,
+            self.sparkline_span.as_secs().to_string(),
+        );
+        storage.set_string(
+            SPARKLINE_AVERAGE_KEY,
+            self.show_sparkline_average.to_string(),
+        );
+    }
 
-                        // Upload speed
-                        ui.group(|ui| {
-                            ui.vertical(|ui| {
-                                ui.label(
-                                    RichText::new("Upload").color(Color32::from_rgb(200, 100, 20)),
-                                );
-                                let speed_text = format_bytes(stats.upload_speed);
-                                let speed_color = speed_color(stats.upload_speed);
-                                // Ensure the same minimum width as download
-                                const SPEED_MIN_W: f32 = 110.0;
-                                const SPEED_H: f32 = 28.0;
-                                ui.add_sized(
-                                    [SPEED_MIN_W, SPEED_H],
-                                    egui::Label::new(
-                                        RichText::new(speed_text)
-                                            .color(speed_color)
-                                            .size(18.0)
-                                            .strong(),
-                                    ),
-                                );
-                            });
-                        });
-                    });
-                });
+    // Uptime and the age of the last refresh. A refresh much older than the
+    // interval means the monitor may be stalled, so it is flagged in red.
+    fn show_status_bar(&self, ui: &mut egui::Ui, interval: Duration) {
+        use egui::RichText;
 
-                ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!(
+                    "Uptime {}",
+                    format_uptime(self.start_time.elapsed())
+                ))
+                .small(),
+            );
+            ui.separator();
+            let age = self.last_update.elapsed();
+            let text =
+                RichText::new(format!("Last refresh {:.1} s ago", age.as_secs_f64())).small();
+            ui.label(if self.paused {
+                text.weak()
+            } else if age > interval * 3 {
+                text.color(egui::Color32::from_rgb(220, 50, 50))
+            } else {
+                text
+            });
+            if let Some(warning) = self.network_monitor.fallback_warning() {
+                ui.separator();
+                ui.label(
+                    RichText::new(format!("⚠ {warning}"))
+                        .small()
+                        .color(egui::Color32::from_rgb(220, 160, 0)),
+                );
             }
         });
     }
-}
 
-fn main() -> Result<(), eframe::Error> {
-    // Estimate an initial window width based on the top control line (search, sort, labels).
-    // This is a simple heuristic (avg char width * chars + padding) that adapts the
-    // initial size to the UI content so the first line is unlikely to be clipped.
-    fn estimate_initial_width() -> f32 {
-        // Rough average character width in px for typical UI font.
-        const AVG_CHAR_W: f32 = 8.0;
-
-        // Components we display on the first row (approximate character counts):
-        let search_label = "Search:".len();
-        let search_box_chars = 10; // user requested ~10 ASCII chars
-        let sort_label = "Sort:".len();
-        let name_label = "Name".len();
-        let download_label = "Download".len();
-        let always_label = "Always on top".len();
-        let total_label = "Total interfaces: 999".len(); // reserve space for counts
-
-        let char_count = search_label
-            + search_box_chars
-            + sort_label
-            + name_label
-            + download_label
-            + always_label
-            + total_label;
-
-        // Add padding for separators, margins and icon area
-        let padding = 140.0_f32;
-        let width = (char_count as f32) * AVG_CHAR_W + padding;
-
-        // Clamp to reasonable bounds
-        width.clamp(420.0, 1400.0)
-    }
-
-    let initial_width = estimate_initial_width();
+    // Visible interface with the highest combined speed, if any is moving data
+    fn busiest_interface(&self) -> Option<&NetworkStats> {
+        self.visible_interfaces()
+            .into_iter()
+            .map(|(_i, s)| (s, s.download_speed + s.upload_speed))
+            .filter(|(_s, speed)| *speed > 0.0)
+            .max_by(|(_, a), (_, b)| compare_speeds(*a, *b))
+            .map(|(s, _speed)| s)
+    }
 
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([initial_width, 360.0])
-            .with_min_inner_size([450.0, 300.0])
-            .with_always_on_top()
-            .with_window_level(egui::WindowLevel::AlwaysOnTop)
-            .with_icon(eframe::icon_data::from_png_bytes(&[]).unwrap_or_default()),
-        ..Default::default()
-    };
+    fn show_compact(&mut self, ui: &mut egui::Ui) {
+        use egui::RichText;
 
-    eframe::run_native(
-        "Speedy - Network Speed Monitor",
-        options,
-        Box::new(|cc| {
-            // Load Chinese fonts for better character support
-            #[cfg(target_os = "windows")]
-            let mut fonts = egui::FontDefinitions::default();
-            #[cfg(not(target_os = "windows"))]
-            let fonts = egui::FontDefinitions::default();
+        ui.horizontal_centered(|ui| {
+            match self.busiest_interface() {
+                Some(stats) => {
+                    let mut speeds = [("⬇", stats.download_speed), ("⬆", stats.upload_speed)];
+                    if self.upload_first {
+                        speeds.reverse();
+                    }
+                    for (arrow, speed) in speeds {
+                        ui.label(
+                            self.speed_text(format!(
+                                "{arrow} {}",
+                                format_bytes(speed, self.rate_format)
+                            ))
+                            .color(self.speed_color(speed))
+                            .strong(),
+                        );
+                    }
+                    ui.label(RichText::new(self.display_name(stats)).small().weak());
+                }
+                None => {
+                    ui.label(RichText::new("all idle").weak());
+                }
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .small_button("⊞")
+                    .on_hover_text("Back to the full view")
+                    .clicked()
+                {
+                    self.compact = false;
+                    ui.ctx()
+                        .send_viewport_cmd(egui::ViewportCommand::MinInnerSize(MIN_WINDOW_SIZE));
+                    if let Some(size) = self.window_size {
+                        ui.ctx()
+                            .send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+                    }
+                }
+            });
+        });
+    }
 
-            // Add system fonts that support Chinese characters
-            #[cfg(target_os = "windows")]
-            {
-                // Try to load system fonts that support Chinese
-                if let Ok(font_data) = std::fs::read("C:\\Windows\\Fonts\\msyh.ttc") {
-                    fonts.font_data.insert(
-                        "Microsoft YaHei".to_owned(),
-                        egui::FontData::from_owned(font_data).into(),
-                    );
+    fn apply_compact_size(&self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(COMPACT_WINDOW_SIZE));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(COMPACT_WINDOW_SIZE));
+    }
 
-                    // Set as primary font for better Chinese support
-                    fonts
-                        .families
-                        .get_mut(&egui::FontFamily::Proportional)
-                        .unwrap()
-                        .insert(0, "Microsoft YaHei".to_owned());
-                } else if let Ok(font_data) = std::fs::read("C:\\Windows\\Fonts\\simhei.ttf") {
-                    fonts.font_data.insert(
-                        "SimHei".to_owned(),
-                        egui::FontData::from_owned(font_data).into(),
-                    );
+    fn palette(&self) -> &'static Palette {
+        match self.palette {
+            PaletteKind::Normal => &NORMAL_PALETTE,
+            PaletteKind::Colorblind => &COLORBLIND_PALETTE,
+        }
+    }
 
-                    fonts
-                        .families
-                        .get_mut(&egui::FontFamily::Proportional)
-                        .unwrap()
-                        .insert(0, "SimHei".to_owned());
-                }
-            }
+    // Color for a speed value with the configured thresholds
+    fn speed_color(&self, value: f64) -> egui::Color32 {
+        self.palette()
+            .speed_color(value, self.medium_speed, self.fast_speed)
+    }
 
-            cc.egui_ctx.set_fonts(fonts);
+    // Explains `speed_color`, with the thresholds in the display unit
+    fn speed_color_hint(&self) -> String {
+        let [fast, medium, idle] = self.palette().speed_color_names;
+        format!(
+            "Above {}: {fast}\nAbove {}: {medium}\nOtherwise: {idle}",
+            format_bytes(self.fast_speed, self.rate_format),
+            format_bytes(self.medium_speed, self.rate_format)
+        )
+    }
 
-            // Initialize app and restore saved settings (sort mode)
-            let mut app = SpeedyApp::default();
-            if let Some(storage) = &cc.storage
-                && let Some(val) = storage.get_string(STORAGE_KEY)
-            {
-                app.sort_mode = match val.as_str() {
-                    "Download" => SortMode::Download,
-                    _ => SortMode::Name,
-                }
-            }
+    // Text for a speed value, in the monospace font if enabled
+    fn speed_text(&self, text: impl Into<String>) -> egui::RichText {
+        let text = egui::RichText::new(text);
+        if self.monospace_speeds {
+            text.family(egui::FontFamily::Monospace)
+        } else {
+            text
+        }
+    }
 
-            Ok(Box::new(app))
-        }),
+    fn window_level(&self) -> egui::WindowLevel {
+        if self.always_on_top {
+            egui::WindowLevel::AlwaysOnTop
+        } else {
+            egui::WindowLevel::Normal
+        }
+    }
+
+    fn load_window_geometry(&mut self, storage: &dyn eframe::Storage) {
+        let load = |key| settings::load::<f32>(storage, key).filter(|v| v.is_finite());
+        if let (Some(x), Some(y)) = (load(WINDOW_X_KEY), load(WINDOW_Y_KEY)) {
+            self.window_pos = Some(egui::pos2(x, y));
+        }
+        if let (Some(w), Some(h)) = (load(WINDOW_W_KEY), load(WINDOW_H_KEY))
+            && w > 0.0
+            && h > 0.0
+        {
+            self.window_size = Some(egui::vec2(w, h));
+        }
+    }
+
+    // Register the entered combo, replacing the previous one
+    fn apply_hotkey(&mut self) {
+        if let Some(hotkey) = &mut self.hotkey {
+            self.hotkey_error = hotkey.set(&self.hotkey_combo).err();
+        }
+    }
+
+    // Quota usage carried over from the last run. A stale cycle is dropped on
+    // the next `Quota::add`.
+    fn load_quota_usage(&mut self, storage: &dyn eframe::Storage) {
+        if let (Some(used), Some(cycle_start)) = (
+            settings::load(storage, QUOTA_USED_KEY),
+            settings::load(storage, QUOTA_CYCLE_KEY),
+        ) {
+            self.quota.used = used;
+            self.quota.cycle_start = Some(cycle_start);
+        }
+    }
+
+    // Without a saved size, widen the window so the search and sort controls
+    // fit on the first line. Measured with the real fonts and spacing, so it
+    // holds for any font or UI scale.
+    fn fit_width_to_controls(ctx: &egui::Context) {
+        let style = ctx.style();
+        let spacing = &style.spacing;
+        let font = egui::TextStyle::Button.resolve(&style);
+        let text_width = |text: &str| {
+            ctx.fonts(|fonts| {
+                fonts
+                    .layout_no_wrap(text.to_string(), font.clone(), egui::Color32::PLACEHOLDER)
+                    .size()
+                    .x
+            })
+        };
+
+        // Mirrors the start of the control row in `update`
+        let buttons = [
+            "⊟ Compact",
+            "re",
+            "Name",
+            "Download",
+            "Upload",
+            "Total",
+            "⬇",
+        ];
+        let labels = ["Search:", "Subnet:", "Min speed:", "Sort:"];
+        let text_edits = [80.0, 100.0]; // their desired widths
+        let separators = 2;
+        let checkboxes = ["Default first", "Active first"];
+        let items =
+            buttons.len() + labels.len() + text_edits.len() + separators + checkboxes.len() + 1;
+
+        let mut width = buttons
+            .iter()
+            .map(|text| text_width(text) + 2.0 * spacing.button_padding.x)
+            .sum::<f32>()
+            + labels.iter().map(|text| text_width(text)).sum::<f32>()
+            + text_edits.iter().map(|w| w + 8.0).sum::<f32>() // plus their margins
+            // "Min speed" slider and its value
+            + spacing.slider_width
+            + spacing.interact_size.x
+            + spacing.item_spacing.x
+            + checkboxes
+                .iter()
+                .map(|text| spacing.icon_width + spacing.icon_spacing + text_width(text))
+                .sum::<f32>()
+            + separators as f32 * 6.0
+            + (items - 1) as f32 * spacing.item_spacing.x
+            + spacing.window_margin.sum().x;
+        let max_width = ctx
+            .input(|i| i.viewport().monitor_size)
+            .map_or(1400.0, |monitor| monitor.x);
+        width = width.clamp(MIN_WINDOW_SIZE.x, max_width.max(MIN_WINDOW_SIZE.x));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
+            width.ceil(),
+            INITIAL_WINDOW_SIZE.y,
+        )));
+    }
+
+    // Move and resize the window to the saved geometry. A position that would
+    // leave the window off-screen (e.g. a monitor was unplugged) is dropped so
+    // the window opens where the platform puts it by default.
+    fn restore_window_geometry(&self, ctx: &egui::Context) {
+        if let Some(size) = self.window_size {
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        }
+        let Some(pos) = self.window_pos else {
+            return;
+        };
+        // Require at least this much of the window to remain reachable
+        const MIN_VISIBLE: f32 = 50.0;
+        let on_screen = match ctx.input(|i| i.viewport().monitor_size) {
+            Some(monitor) => {
+                let width = self.window_size.map_or(MIN_VISIBLE, |size| size.x);
+                pos.x + width >= MIN_VISIBLE
+                    && pos.x <= monitor.x - MIN_VISIBLE
+                    && pos.y >= 0.0
+                    && pos.y <= monitor.y - MIN_VISIBLE
+            }
+            None => pos.x >= 0.0 && pos.y >= 0.0,
+        };
+        if on_screen {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+        }
+    }
+
+    fn load_settings(&mut self, storage: &dyn eframe::Storage) {
+        if let Some(val) = storage.get_string(STORAGE_KEY) {
+            self.sort_mode = match val.as_str() {
+                "Download" => SortMode::Download,
+                "Upload" => SortMode::Upload,
+                "Total" => SortMode::Total,
+                _ => SortMode::Name,
+            }
+        }
+        if let Some(descending) = settings::load(storage, SORT_DESCENDING_KEY) {
+            self.sort_descending = descending;
+        } else if storage.get_string(STORAGE_KEY).is_some() {
+            self.sort_descending = self.sort_mode.default_descending();
+        }
+        if let Some(on_top) = settings::load(storage, ALWAYS_ON_TOP_KEY) {
+            self.always_on_top = on_top;
+        }
+        if let Some(secs) =
+            settings::load::<f64>(storage, ACTIVE_GRACE_KEY).filter(|v| v.is_finite() && *v >= 0.0)
+        {
+            self.network_monitor
+                .set_active_grace(Duration::from_secs_f64(secs));
+        }
+        if let Some(ms) = settings::load::<u64>(storage, INTERVAL_KEY).filter(|ms| *ms > 0) {
+            self.update_interval = Duration::from_millis(ms);
+        }
+        if let Some(adaptive) = settings::load(storage, ADAPTIVE_KEY) {
+            self.adaptive_interval = adaptive;
+        }
+        if let Some(ms) = settings::load::<u64>(storage, ADAPTIVE_FAST_KEY).filter(|ms| *ms > 0) {
+            self.fast_interval = Duration::from_millis(ms);
+        }
+        if let Some(ms) = settings::load::<u64>(storage, ADAPTIVE_SLOW_KEY).filter(|ms| *ms > 0) {
+            self.slow_interval = Duration::from_millis(ms);
+        }
+        if let Some(threshold) = settings::load::<f64>(storage, ADAPTIVE_THRESHOLD_KEY)
+            .filter(|v| v.is_finite() && *v >= 0.0)
+        {
+            self.adaptive_threshold = threshold;
+        }
+        if let Some(smoothing) = settings::load(storage, SMOOTHING_KEY) {
+            self.network_monitor.set_smoothing(smoothing);
+        }
+        if let Some(alpha) = settings::load::<f64>(storage, SMOOTHING_ALPHA_KEY)
+            .filter(|v| v.is_finite() && *v > 0.0)
+        {
+            self.network_monitor.set_smoothing_alpha(alpha);
+        }
+        if let Some(subnet) = storage.get_string(SUBNET_FILTER_KEY) {
+            self.subnet_filter = subnet;
+        }
+        // An empty query is saved too, and lists everything as usual
+        if let Some(query) = storage.get_string(SEARCH_QUERY_KEY) {
+            self.search_query = query;
+            self.compile_search_regex();
+        }
+        if let Some(enabled) = settings::load(storage, ALERT_KEY) {
+            self.alert_enabled = enabled;
+        }
+        if let Some(threshold) = settings::load::<f64>(storage, ALERT_THRESHOLD_KEY)
+            .filter(|v| v.is_finite() && *v > 0.0)
+        {
+            self.alert_threshold = threshold;
+        }
+        if let Some(watch) = settings::load(storage, UPLOAD_WATCH_KEY) {
+            self.upload_watch = watch;
+        }
+        if let Some(threshold) = settings::load::<f64>(storage, UPLOAD_WATCH_THRESHOLD_KEY)
+            .filter(|v| v.is_finite() && *v > 0.0)
+        {
+            self.upload_watch_threshold = threshold;
+        }
+        if let Some(secs) = settings::load::<u64>(storage, UPLOAD_WATCH_SECS_KEY).filter(|s| *s > 0)
+        {
+            self.upload_watch_for = Duration::from_secs(secs);
+        }
+        if let Some(notify) = settings::load(storage, UPLOAD_WATCH_NOTIFY_KEY) {
+            self.upload_watch_notify = notify;
+        }
+        if let Some(collapsed) = storage.get_string(COLLAPSED_KEY) {
+            self.collapsed = collapsed
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(hidden) = storage.get_string(HIDDEN_INTERFACES_KEY) {
+            self.hidden_interfaces = hidden
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(sum) = settings::load(storage, SUM_SELECTED_KEY) {
+            self.sum_selected = sum;
+        }
+        if let Some(selected) = storage.get_string(SELECTED_INTERFACES_KEY) {
+            self.selected_interfaces = selected
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(list) = settings::load(storage, LIST_SELECTED_KEY) {
+            self.list_selected = list;
+        }
+        if let Some(show) = settings::load(storage, SHOW_VIRTUAL_KEY) {
+            self.show_virtual = show;
+        }
+        if let Some(show) = settings::load(storage, SHOW_LOOPBACK_KEY) {
+            self.show_loopback = show;
+        }
+        if let Some(default_first) = settings::load(storage, DEFAULT_FIRST_KEY) {
+            self.default_first = default_first;
+        }
+        if let Some(active_first) = settings::load(storage, ACTIVE_FIRST_KEY) {
+            self.active_first = active_first;
+        }
+        if let Some(min_speed) =
+            settings::load::<f64>(storage, MIN_SPEED_KEY).filter(|v| v.is_finite() && *v >= 0.0)
+        {
+            self.min_speed = min_speed;
+        }
+        if let Some(active_only) = settings::load(storage, ACTIVE_ONLY_KEY) {
+            self.active_only = active_only;
+        }
+        if let Some(secs) = settings::load::<u64>(storage, ACTIVE_ONLY_IDLE_KEY).filter(|s| *s > 0)
+        {
+            self.active_only_idle = Duration::from_secs(secs);
+        }
+        if let Some(highlight) = settings::load(storage, TOTAL_HIGHLIGHT_KEY) {
+            self.total_highlight = highlight;
+        }
+        // Either may be missing, e.g. from a hand-edited config.json
+        let warn = settings::load(storage, TOTAL_WARN_KEY).unwrap_or(self.total_warn_bytes);
+        let high = settings::load(storage, TOTAL_HIGH_KEY).unwrap_or(self.total_high_bytes);
+        if warn < high {
+            self.total_warn_bytes = warn;
+            self.total_high_bytes = high;
+        }
+        let threshold =
+            |key| settings::load::<f64>(storage, key).filter(|v| v.is_finite() && *v >= 0.0);
+        // Either may be missing, e.g. from a hand-edited config.json
+        let medium = threshold(MEDIUM_SPEED_KEY).unwrap_or(self.medium_speed);
+        let fast = threshold(FAST_SPEED_KEY).unwrap_or(self.fast_speed);
+        if medium < fast {
+            self.medium_speed = medium;
+            self.fast_speed = fast;
+        }
+        if let Some(val) = storage.get_string(BACKEND_KEY) {
+            self.network_monitor.set_backend(match val.as_str() {
+                "Sysfs" => Backend::Sysfs,
+                "ProcNetDev" => Backend::ProcNetDev,
+                _ => Backend::Sysinfo,
+            });
+        }
+        #[cfg(target_os = "windows")]
+        if let Some(val) = storage.get_string(CONNECTION_FAMILY_KEY) {
+            use network_monitor::AddressFamily;
+            self.network_monitor
+                .set_connection_family(match val.as_str() {
+                    "IPv4" => AddressFamily::V4,
+                    "IPv6" => AddressFamily::V6,
+                    _ => AddressFamily::Both,
+                });
+        }
+        if let Some(grid) = settings::load(storage, GRID_LAYOUT_KEY) {
+            self.grid_layout = grid;
+        }
+        if let Some(freeze) = settings::load(storage, FREEZE_ON_HOVER_KEY) {
+            self.freeze_on_hover = freeze;
+        }
+        if let Some(upload_first) = settings::load(storage, UPLOAD_FIRST_KEY) {
+            self.upload_first = upload_first;
+        }
+        if let Some(show) = settings::load(storage, HEATMAP_KEY) {
+            self.show_heatmap = show;
+        }
+        if let Some(show) = settings::load(storage, SHOW_DELTAS_KEY) {
+            self.show_deltas = show;
+        }
+        if let Some(monospace) = settings::load(storage, MONOSPACE_KEY) {
+            self.monospace_speeds = monospace;
+        }
+        if let Some(combo) = storage.get_string(HOTKEY_KEY) {
+            self.hotkey_combo = combo;
+        }
+        if let Some(gb) =
+            settings::load::<f64>(storage, QUOTA_GB_KEY).filter(|v| v.is_finite() && *v >= 0.0)
+        {
+            self.quota.limit_gb = gb;
+        }
+        if let Some(day) =
+            settings::load::<u32>(storage, QUOTA_START_DAY_KEY).filter(|d| (1..=31).contains(d))
+        {
+            self.quota.set_start_day(day, quota::Date::today());
+        }
+        if let Some(show) = settings::load(storage, SUMMARY_KEY) {
+            self.show_summary = show;
+        }
+        if let Some(show) = settings::load(storage, TOTAL_GRAPH_KEY) {
+            self.show_total_graph = show;
+        }
+        if let Some(compact) = settings::load(storage, COMPACT_KEY) {
+            self.compact = compact;
+        }
+        if let Some(val) = storage.get_string(PALETTE_KEY) {
+            self.palette = match val.as_str() {
+                "Colorblind" => PaletteKind::Colorblind,
+                _ => PaletteKind::Normal,
+            }
+        }
+        if let Some(val) = storage.get_string(THEME_KEY) {
+            self.theme = match val.as_str() {
+                "System" => egui::ThemePreference::System,
+                "Light" => egui::ThemePreference::Light,
+                _ => egui::ThemePreference::Dark,
+            }
+        }
+        if let Some(secs) = settings::load::<u64>(storage, SPARKLINE_SPAN_KEY) {
+            self.sparkline_span = Duration::from_secs(secs)
+                .clamp(history::MIN_SPARKLINE_SPAN, history::MAX_SPARKLINE_SPAN);
+        }
+        if let Some(show) = settings::load(storage, SPARKLINE_AVERAGE_KEY) {
+            self.show_sparkline_average = show;
+        }
+        if let Some(val) = storage.get_string(SPEED_UNIT_KEY) {
+            self.rate_format.unit = match val.as_str() {
+                "Bits" => SpeedUnit::Bits,
+                _ => SpeedUnit::Bytes,
+            }
+        }
+        if let Some(val) = storage.get_string(TIME_BASE_KEY) {
+            self.rate_format.time_base = match val.as_str() {
+                "Minute" => TimeBase::Minute,
+                "Hour" => TimeBase::Hour,
+                _ => TimeBase::Second,
+            }
+        }
+        if let Some(val) = storage.get_string(PRECISION_KEY) {
+            // "auto" (or anything unrecognised) keeps the magnitude-based default
+            self.rate_format.number.precision = val.parse().ok().filter(|d| *d <= 2);
+        }
+        if let Some(val) = storage.get_string(UNIT_BASE_KEY) {
+            self.rate_format.number.base = match val.as_str() {
+                "Si" => UnitBase::Si,
+                _ => UnitBase::Binary,
+            }
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            self.status_message = Some(format!(
+                "Stopped recording to {}",
+                recorder.path().display()
+            ));
+            return;
+        }
+        let Some(path) = export::pick_save_path("speedy-log.jsonl", "jsonl") else {
+            return;
+        };
+        match export::Recorder::open(path) {
+            Ok(recorder) => {
+                self.status_message = Some(format!("Recording to {}", recorder.path().display()));
+                self.recorder = Some(recorder);
+            }
+            Err(e) => self.status_message = Some(format!("Failed to start recording: {e}")),
+        }
+    }
+
+    fn record_stats(&mut self) {
+        if let Some(recorder) = &mut self.recorder
+            && let Err(e) = recorder.write(&self.network_stats)
+        {
+            self.status_message = Some(format!("Recording stopped: {e}"));
+            self.recorder = None;
+        }
+    }
+
+    // Notify once when an interface's download speed goes from below to above
+    // the threshold; it can alert again after dropping back below.
+    fn check_speed_alerts(&mut self) {
+        if !self.alert_enabled {
+            self.alerted.clear();
+            return;
+        }
+        for stats in &self.network_stats {
+            if stats.download_speed <= self.alert_threshold {
+                self.alerted.remove(&stats.name);
+            } else if self.alerted.insert(stats.name.clone()) {
+                let body = format!(
+                    "{} is downloading at {}",
+                    self.display_name(stats),
+                    format_bytes(stats.download_speed, self.rate_format)
+                );
+                // Showing a notification can block (D-Bus), so keep it off the UI thread
+                std::thread::spawn(move || {
+                    let _ = notify_rust::Notification::new()
+                        .summary("Speedy: high download speed")
+                        .body(&body)
+                        .show();
+                });
+            }
+        }
+    }
+
+    // Flag interfaces whose upload has stayed above the watch threshold for the
+    // configured time; a single refresh below it starts the count over. Going
+    // by time rather than refresh count keeps this right with any interval.
+    fn check_sustained_uploads(&mut self) {
+        if !self.upload_watch {
+            self.upload_over_since.clear();
+            self.upload_flagged.clear();
+            return;
+        }
+        let now = Instant::now();
+        self.upload_over_since
+            .retain(|name, _| self.network_stats.iter().any(|s| &s.name == name));
+        self.upload_flagged
+            .retain(|name| self.network_stats.iter().any(|s| &s.name == name));
+        for stats in &self.network_stats {
+            if stats.upload_speed <= self.upload_watch_threshold {
+                self.upload_over_since.remove(&stats.name);
+                self.upload_flagged.remove(&stats.name);
+                continue;
+            }
+            let since = *self
+                .upload_over_since
+                .entry(stats.name.clone())
+                .or_insert(now);
+            if now.duration_since(since) >= self.upload_watch_for
+                && self.upload_flagged.insert(stats.name.clone())
+                && self.upload_watch_notify
+            {
+                let body = format!(
+                    "{} has been uploading above {} for over {} s",
+                    self.display_name(stats),
+                    format_bytes(self.upload_watch_threshold, self.rate_format),
+                    self.upload_watch_for.as_secs()
+                );
+                // Same as the download alert: keep a blocking notification off the UI thread
+                std::thread::spawn(move || {
+                    let _ = notify_rust::Notification::new()
+                        .summary("Speedy: sustained upload")
+                        .body(&body)
+                        .show();
+                });
+            }
+        }
+    }
+
+    fn update_tray_tooltip(&self) {
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        if let Some(tray) = &self.tray {
+            let (download, upload) = self.network_stats.iter().fold((0.0, 0.0), |(d, u), s| {
+                (d + s.download_speed, u + s.upload_speed)
+            });
+            tray.set_tooltip(&format!(
+                "Speedy\n⬇ {}  ⬆ {}",
+                format_bytes(download, self.rate_format),
+                format_bytes(upload, self.rate_format)
+            ));
+        }
+    }
+
+    fn export_csv(&mut self) {
+        let Some(path) = export::pick_save_path("speedy-stats.csv", "csv") else {
+            return;
+        };
+        self.status_message = Some(match export::write_csv(&path, &self.network_stats) {
+            Ok(()) => format!("Exported stats to {}", path.display()),
+            Err(e) => format!("CSV export failed: {e}"),
+        });
+    }
+
+    // Plain-text summary of the interfaces as listed (filtered and sorted)
+    fn stats_summary(&self) -> String {
+        self.visible_interfaces()
+            .into_iter()
+            .map(|(_i, s)| {
+                format!(
+                    "{}: down {} up {} (total down {} up {})",
+                    self.display_name(s),
+                    format_bytes(s.download_speed, self.rate_format),
+                    format_bytes(s.upload_speed, self.rate_format),
+                    format_total_bytes(s.bytes_received, self.rate_format.number),
+                    format_total_bytes(s.bytes_transmitted, self.rate_format.number)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // Session totals and peak speeds per interface, as a table for stdout
+    fn session_summary(&self) -> String {
+        let mut rows: Vec<(String, &NetworkStats)> = self
+            .network_stats
+            .iter()
+            .map(|s| (self.display_name(s), s))
+            .collect();
+        rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let width = rows
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .chain(["Interface".len()])
+            .max()
+            .unwrap_or_default();
+
+        let mut summary = format!(
+            "Session summary\n{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+            "Interface", "Received", "Sent", "Peak down", "Peak up"
+        );
+        for (name, stats) in rows {
+            let (received, transmitted) = self
+                .sessions
+                .get(&stats.name)
+                .map_or((0, 0), |s| (s.received, s.transmitted));
+            let (down_peak, up_peak) = self.peaks.get(&stats.name).copied().unwrap_or_default();
+            summary.push_str(&format!(
+                "\n{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+                name,
+                format_total_bytes(received, self.rate_format.number),
+                format_total_bytes(transmitted, self.rate_format.number),
+                format_bytes(down_peak, self.rate_format),
+                format_bytes(up_peak, self.rate_format)
+            ));
+        }
+        summary
+    }
+
+    fn export_settings(&self) -> Result<(), String> {
+        let mut exported = MemoryStorage::default();
+        self.save_settings(&mut exported);
+        settings::export_to_file(exported, &PathBuf::from(&self.settings_path))
+    }
+
+    fn import_settings(&mut self, frame: &mut eframe::Frame) -> Result<(), String> {
+        let imported = settings::import_from_file(&PathBuf::from(&self.settings_path))?;
+        self.load_settings(&imported);
+        if let Some(storage) = frame.storage_mut() {
+            self.save_settings(storage);
+            storage.flush();
+        }
+        Ok(())
+    }
+
+    fn show_settings_window(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        let mut open = self.show_settings;
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Active grace period:");
+                    let mut secs = self.network_monitor.active_grace().as_secs_f64();
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut secs)
+                                .range(0.0..=60.0)
+                                .speed(0.1)
+                                .suffix(" s"),
+                        )
+                        .on_hover_text("How long an interface stays active after its last traffic")
+                        .changed()
+                    {
+                        self.network_monitor
+                            .set_active_grace(Duration::from_secs_f64(secs));
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("\"Active only\" hides after idle for:");
+                    let mut secs = self.active_only_idle.as_secs();
+                    if ui
+                        .add(egui::DragValue::new(&mut secs).range(1..=3600).suffix(" s"))
+                        .changed()
+                    {
+                        self.active_only_idle = Duration::from_secs(secs);
+                    }
+                });
+
+                let rate_format = self.rate_format;
+                ui.horizontal(|ui| {
+                    ui.label("Show rates per:");
+                    ui.selectable_value(
+                        &mut self.rate_format.time_base,
+                        TimeBase::Second,
+                        "second",
+                    );
+                    ui.selectable_value(
+                        &mut self.rate_format.time_base,
+                        TimeBase::Minute,
+                        "minute",
+                    );
+                    ui.selectable_value(&mut self.rate_format.time_base, TimeBase::Hour, "hour");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Decimals:");
+                    let precision = &mut self.rate_format.number.precision;
+                    ui.selectable_value(precision, None, "auto")
+                        .on_hover_text("More decimals for smaller numbers");
+                    for decimals in 0..=2 {
+                        ui.selectable_value(precision, Some(decimals), decimals.to_string());
+                    }
+                });
+                if self.rate_format != rate_format {
+                    self.update_rate_units();
+                }
+                ui.checkbox(
+                    &mut self.show_sparkline_average,
+                    "Show average trend on sparklines",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Sparklines show the last");
+                    let mut secs = self.sparkline_span.as_secs();
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut secs)
+                                .range(
+                                    history::MIN_SPARKLINE_SPAN.as_secs()
+                                        ..=history::MAX_SPARKLINE_SPAN.as_secs(),
+                                )
+                                .suffix(" s"),
+                        )
+                        .changed()
+                    {
+                        self.sparkline_span = Duration::from_secs(secs);
+                    }
+                });
+                ui.checkbox(&mut self.show_heatmap, "Show activity heatmap strip");
+                ui.checkbox(
+                    &mut self.show_deltas,
+                    "Show bytes moved since the last refresh",
+                );
+                ui.checkbox(&mut self.monospace_speeds, "Fixed-width speed digits")
+                    .on_hover_text("Keeps the layout from shifting as speeds change");
+                ui.checkbox(&mut self.grid_layout, "Grid layout on wide windows");
+                ui.checkbox(
+                    &mut self.freeze_on_hover,
+                    "Hold a card's values while hovering it",
+                )
+                .on_hover_text("Other cards keep updating");
+                ui.checkbox(&mut self.upload_first, "Upload before download");
+
+                ui.separator();
+
+                // Speed color thresholds; medium has to stay below fast
+                ui.horizontal(|ui| {
+                    let [fast, medium, _idle] = self.palette().speed_color_names;
+                    let mut medium_kb = self.medium_speed / 1024.0;
+                    let mut fast_mb = self.fast_speed / (1024.0 * 1024.0);
+                    ui.label(format!("Speed colors: {medium} above"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut medium_kb)
+                                .range(0.0..=f64::MAX)
+                                .suffix(" KB/s"),
+                        )
+                        .changed()
+                        && medium_kb * 1024.0 < self.fast_speed
+                    {
+                        self.medium_speed = medium_kb * 1024.0;
+                    }
+                    ui.label(format!("{fast} above"));
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut fast_mb)
+                                .range(0.0..=f64::MAX)
+                                .speed(0.1)
+                                .suffix(" MB/s"),
+                        )
+                        .changed()
+                        && fast_mb * 1024.0 * 1024.0 > self.medium_speed
+                    {
+                        self.fast_speed = fast_mb * 1024.0 * 1024.0;
+                    }
+                    if ui.small_button("Reset").clicked() {
+                        self.medium_speed = DEFAULT_MEDIUM_SPEED;
+                        self.fast_speed = DEFAULT_FAST_SPEED;
+                    }
+                });
+
+                ui.separator();
+
+                // Highlighting of large lifetime totals; amber has to stay below red
+                ui.checkbox(&mut self.total_highlight, "Highlight large totals");
+                ui.add_enabled_ui(self.total_highlight, |ui| {
+                    ui.horizontal(|ui| {
+                        let mut warn_gb = self.total_warn_bytes as f64 / GB;
+                        let mut high_gb = self.total_high_bytes as f64 / GB;
+                        ui.label("Amber above:");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut warn_gb)
+                                    .range(0.0..=f64::MAX)
+                                    .speed(0.1)
+                                    .suffix(" GB"),
+                            )
+                            .changed()
+                            && ((warn_gb * GB) as u64) < self.total_high_bytes
+                        {
+                            self.total_warn_bytes = (warn_gb * GB) as u64;
+                        }
+                        ui.label("Red above:");
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut high_gb)
+                                    .range(0.0..=f64::MAX)
+                                    .speed(0.1)
+                                    .suffix(" GB"),
+                            )
+                            .changed()
+                            && (high_gb * GB) as u64 > self.total_warn_bytes
+                        {
+                            self.total_high_bytes = (high_gb * GB) as u64;
+                        }
+                    });
+                });
+
+                if cfg!(target_os = "linux") {
+                    ui.horizontal(|ui| {
+                        ui.label("Counter source:");
+                        let mut backend = self.network_monitor.backend();
+                        ui.selectable_value(&mut backend, Backend::Sysinfo, "sysinfo");
+                        ui.selectable_value(&mut backend, Backend::Sysfs, "sysfs")
+                            .on_hover_text("Read /sys/class/net/<iface>/statistics directly");
+                        ui.selectable_value(&mut backend, Backend::ProcNetDev, "/proc/net/dev")
+                            .on_hover_text("Read /proc/net/dev directly, bypassing sysinfo");
+                        self.network_monitor.set_backend(backend);
+                    });
+                }
+                #[cfg(target_os = "windows")]
+                ui.horizontal(|ui| {
+                    use network_monitor::AddressFamily;
+                    ui.label("Count connections:");
+                    let mut family = self.network_monitor.connection_family();
+                    ui.selectable_value(&mut family, AddressFamily::Both, "All");
+                    ui.selectable_value(&mut family, AddressFamily::V4, "IPv4");
+                    ui.selectable_value(&mut family, AddressFamily::V6, "IPv6");
+                    self.network_monitor.set_connection_family(family);
+                });
+
+                ui.separator();
+
+                // Adaptive refresh: fast while busy, slow while idle
+                ui.checkbox(&mut self.adaptive_interval, "Adaptive refresh interval");
+                ui.add_enabled_ui(self.adaptive_interval, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Fast:");
+                        let mut fast_ms = self.fast_interval.as_millis() as u64;
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut fast_ms)
+                                    .range(100..=5000)
+                                    .suffix(" ms"),
+                            )
+                            .changed()
+                        {
+                            self.fast_interval = Duration::from_millis(fast_ms);
+                        }
+                        ui.label("Slow:");
+                        let mut slow_ms = self.slow_interval.as_millis() as u64;
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut slow_ms)
+                                    .range(100..=60000)
+                                    .suffix(" ms"),
+                            )
+                            .changed()
+                        {
+                            self.slow_interval = Duration::from_millis(slow_ms);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Busy above:");
+                        let mut kb = self.adaptive_threshold / 1024.0;
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut kb)
+                                    .range(0.0..=f64::MAX)
+                                    .suffix(" KB/s"),
+                            )
+                            .on_hover_text(
+                                "Combined speed of all interfaces; back to slow below half of this",
+                            )
+                            .changed()
+                        {
+                            self.adaptive_threshold = kb * 1024.0;
+                        }
+                    });
+                });
+
+                ui.separator();
+
+                // Weight of each new sample when "Smooth" is on
+                ui.horizontal(|ui| {
+                    ui.label("Smoothing factor:");
+                    let mut alpha = self.network_monitor.smoothing_alpha();
+                    if ui
+                        .add(egui::Slider::new(&mut alpha, 0.05..=1.0))
+                        .on_hover_text("Lower is smoother but slower to react; 1 is unsmoothed")
+                        .changed()
+                    {
+                        self.network_monitor.set_smoothing_alpha(alpha);
+                    }
+                });
+
+                ui.separator();
+
+                // System-wide show/hide shortcut
+                ui.horizontal(|ui| {
+                    ui.label("Show/hide hotkey:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.hotkey_combo)
+                            .hint_text("none")
+                            .desired_width(120.0),
+                    )
+                    .on_hover_text("Off until set, e.g. Ctrl+Alt+Shift+S or Alt+F12");
+                    if ui.button("Apply").clicked() {
+                        self.apply_hotkey();
+                    }
+                });
+                if self.hotkey.is_none() {
+                    ui.label(
+                        egui::RichText::new("Global hotkeys aren't available on this system")
+                            .small()
+                            .weak(),
+                    );
+                } else if let Some(err) = &self.hotkey_error {
+                    ui.label(
+                        egui::RichText::new(err)
+                            .small()
+                            .color(egui::Color32::from_rgb(220, 50, 50)),
+                    );
+                }
+
+                ui.separator();
+
+                // Sustained uploads, e.g. something sending data out unexpectedly
+                ui.checkbox(&mut self.upload_watch, "Flag sustained uploads");
+                ui.add_enabled_ui(self.upload_watch, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Above");
+                        let mut mb = self.upload_watch_threshold / (1024.0 * 1024.0);
+                        if ui
+                            .add(
+                                egui::DragValue::new(&mut mb)
+                                    .range(0.01..=100_000.0)
+                                    .speed(0.1)
+                                    .suffix(" MB/s"),
+                            )
+                            .changed()
+                        {
+                            self.upload_watch_threshold = mb * 1024.0 * 1024.0;
+                        }
+                        ui.label("for");
+                        let mut secs = self.upload_watch_for.as_secs();
+                        if ui
+                            .add(egui::DragValue::new(&mut secs).range(1..=3600).suffix(" s"))
+                            .changed()
+                        {
+                            self.upload_watch_for = Duration::from_secs(secs);
+                        }
+                        ui.checkbox(&mut self.upload_watch_notify, "Notify");
+                    });
+                });
+
+                ui.separator();
+
+                // Monthly data cap on the default-route interface
+                ui.horizontal(|ui| {
+                    ui.label("Monthly quota:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.quota.limit_gb)
+                            .range(0.0..=100_000.0)
+                            .speed(1.0)
+                            .suffix(" GB"),
+                    )
+                    .on_hover_text("0 turns quota tracking off");
+                    ui.label("resets on day");
+                    let mut start_day = self.quota.start_day;
+                    if ui
+                        .add(egui::DragValue::new(&mut start_day).range(1..=31))
+                        .changed()
+                    {
+                        self.quota.set_start_day(start_day, quota::Date::today());
+                    }
+                    if ui.button("Reset usage").clicked() {
+                        self.quota.reset();
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(
+                        "Traffic is only counted while speedy is running and not paused",
+                    )
+                    .small()
+                    .weak(),
+                );
+
+                ui.separator();
+
+                // Export/import the full settings set as a JSON file
+                ui.horizontal(|ui| {
+                    ui.label("Settings file:");
+                    ui.text_edit_singleline(&mut self.settings_path);
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Export settings").clicked() {
+                        self.settings_status = Some(match self.export_settings() {
+                            Ok(()) => format!("Exported to {}", self.settings_path),
+                            Err(e) => e,
+                        });
+                    }
+                    if ui.button("Import settings").clicked() {
+                        self.settings_status = Some(match self.import_settings(frame) {
+                            Ok(()) => {
+                                // The imported file may change the window level, mode or theme
+                                ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                                    self.window_level(),
+                                ));
+                                if self.compact {
+                                    self.apply_compact_size(ctx);
+                                }
+                                ctx.set_theme(self.theme);
+                                self.apply_hotkey();
+                                format!("Imported from {}", self.settings_path)
+                            }
+                            Err(e) => e,
+                        });
+                    }
+                });
+                if let Some(status) = &self.settings_status {
+                    ui.label(status);
+                }
+            });
+        self.show_settings = open;
+    }
+
+    // The subnet filter, if one is entered. An empty input means no filter.
+    fn parsed_subnet_filter(&self) -> Result<Option<Subnet>, String> {
+        if self.subnet_filter.trim().is_empty() {
+            Ok(None)
+        } else {
+            self.subnet_filter.parse().map(Some)
+        }
+    }
+
+    // Emphasis color for a lifetime byte total, or `None` to leave it plain.
+    // Mirrors `speed_color` but with its own, user-configured thresholds.
+    fn total_color(&self, bytes: u64) -> Option<egui::Color32> {
+        if !self.total_highlight {
+            None
+        } else if bytes > self.total_high_bytes {
+            Some(egui::Color32::from_rgb(220, 50, 50))
+        } else if bytes > self.total_warn_bytes {
+            Some(egui::Color32::from_rgb(200, 150, 0))
+        } else {
+            None
+        }
+    }
+
+    // Name to show for an interface. Privacy mode replaces real names with
+    // generic labels numbered in name order; everything else stays keyed by
+    // the real name.
+    fn display_name(&self, stats: &NetworkStats) -> String {
+        if !self.privacy_mode {
+            return stats.display_name.clone();
+        }
+        let position = self
+            .network_stats
+            .iter()
+            .filter(|s| s.name < stats.name)
+            .count();
+        format!("Interface {}", position + 1)
+    }
+
+    // Collapsible checkbox list of every discovered interface. Only unchecked
+    // names are remembered, so new interfaces show up by default.
+    fn show_interface_toggles(&mut self, ui: &mut egui::Ui) {
+        let mut names: Vec<(String, &str)> = self
+            .network_stats
+            .iter()
+            .map(|s| (self.display_name(s), s.name.as_str()))
+            .collect();
+        names.sort();
+        let mut toggled = Vec::new();
+        let mut sum_selected = self.sum_selected;
+        let mut list_selected = self.list_selected;
+        let title = if self.sum_selected {
+            format!("Interfaces ({} summed)", self.selected_interfaces.len())
+        } else {
+            format!("Interfaces ({} hidden)", self.hidden_interfaces.len())
+        };
+        egui::CollapsingHeader::new(title)
+            .id_salt("interface_toggles")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut sum_selected, false, "Hide unchecked");
+                    ui.radio_value(&mut sum_selected, true, "Sum checked")
+                        .on_hover_text("Combine the checked interfaces into a \"Selected\" total");
+                    if sum_selected {
+                        ui.checkbox(&mut list_selected, "Also list them individually");
+                    }
+                });
+                ui.horizontal_wrapped(|ui| {
+                    for (label, name) in &names {
+                        let mut checked = if self.sum_selected {
+                            self.selected_interfaces.contains(*name)
+                        } else {
+                            !self.hidden_interfaces.contains(*name)
+                        };
+                        if ui.checkbox(&mut checked, label).changed() {
+                            toggled.push(name.to_string());
+                        }
+                    }
+                });
+            });
+        // Each mode keeps its own set, so switching back and forth loses nothing
+        let set = if self.sum_selected {
+            &mut self.selected_interfaces
+        } else {
+            &mut self.hidden_interfaces
+        };
+        for name in toggled {
+            if !set.remove(&name) {
+                set.insert(name);
+            }
+        }
+        self.sum_selected = sum_selected;
+        self.list_selected = list_selected;
+    }
+
+    // Recompile the search pattern after an edit. An invalid pattern keeps the
+    // previous one in effect so the list doesn't jump around while typing.
+    fn compile_search_regex(&mut self) {
+        self.search_error = None;
+        if !self.regex_search {
+            return;
+        }
+        if self.search_query.is_empty() {
+            self.search_regex = None;
+            return;
+        }
+        match regex::RegexBuilder::new(&self.search_query)
+            .case_insensitive(true)
+            .build()
+        {
+            Ok(regex) => self.search_regex = Some(regex),
+            Err(err) => self.search_error = Some(err.to_string()),
+        }
+    }
+
+    // Interfaces to display, after applying the search filter and sort order
+    fn visible_interfaces(&self) -> Vec<(usize, &NetworkStats)> {
+        // Sort interfaces according to user's choice. We create a vector
+        // of (index, &NetworkStats) so we can use the original index as
+        // a stable tiebreaker.
+        let mut indexed: Vec<(usize, &NetworkStats)> = self
+            .network_stats
+            .iter()
+            .enumerate()
+            .filter(|(_i, s)| {
+                if self.sum_selected {
+                    self.list_selected || !self.selected_interfaces.contains(&s.name)
+                } else {
+                    !self.hidden_interfaces.contains(&s.name)
+                }
+            })
+            // Interfaces that never had traffic count as idle
+            .filter(|(_i, s)| {
+                !self.active_only
+                    || s.idle_time
+                        .is_some_and(|idle| idle <= self.active_only_idle)
+            })
+            .collect();
+
+        // Apply search filter (case-insensitive) before sorting
+        let query = self.search_query.to_lowercase();
+        if self.regex_search {
+            if let Some(regex) = &self.search_regex {
+                indexed
+                    .retain(|(_i, s)| regex.is_match(&s.name) || regex.is_match(&s.display_name));
+            }
+        } else if !query.is_empty() {
+            indexed.retain(|(_i, s)| {
+                s.name.to_lowercase().contains(&query)
+                    || s.display_name.to_lowercase().contains(&query)
+            });
+        }
+
+        // Keep interfaces with at least one address in the subnet (invalid input doesn't filter)
+        if let Ok(Some(subnet)) = self.parsed_subnet_filter() {
+            indexed.retain(|(_i, s)| s.ip_addresses.iter().any(|ip| subnet.contains(ip)));
+        }
+
+        if self.min_speed > 0.0 {
+            indexed.retain(|(_i, s)| s.download_speed + s.upload_speed >= self.min_speed);
+        }
+
+        indexed.sort_by(|(i, a), (j, b)| {
+            // Pinned to the top regardless of direction
+            let pinned = if self.sort_mode == SortMode::Name && self.default_first {
+                b.is_default.cmp(&a.is_default)
+            } else {
+                Ordering::Equal
+            };
+            // Active before idle, whatever the direction
+            let active = if self.active_first {
+                b.is_active.cmp(&a.is_active)
+            } else {
+                Ordering::Equal
+            };
+            // Ascending order of the sort key
+            let ord = match self.sort_mode {
+                SortMode::Name => a
+                    .display_name
+                    .to_lowercase()
+                    .cmp(&b.display_name.to_lowercase()),
+                SortMode::Download => compare_speeds(a.download_speed, b.download_speed),
+                SortMode::Upload => compare_speeds(a.upload_speed, b.upload_speed),
+                SortMode::Total => {
+                    // Lifetime bytes moved in both directions
+                    let total =
+                        |s: &NetworkStats| s.bytes_received.saturating_add(s.bytes_transmitted);
+                    total(a).cmp(&total(b))
+                }
+            };
+            let ord = if self.sort_descending {
+                ord.reverse()
+            } else {
+                ord
+            };
+            // Ties keep their original order in either direction
+            pinned.then(active).then(ord).then_with(|| i.cmp(j))
+        });
+
+        indexed
+    }
+
+    // One cell per visible interface, colored by combined speed. Hovering shows
+    // the details and clicking scrolls the list to that interface.
+    fn show_heatmap_strip(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.spacing_mut().item_spacing.y = 2.0;
+            for (_idx, stats) in self.visible_interfaces() {
+                let speed = stats.download_speed + stats.upload_speed;
+                let (rect, response) = ui.allocate_exact_size(
+                    egui::vec2(HEATMAP_CELL, HEATMAP_CELL),
+                    egui::Sense::click(),
+                );
+                ui.painter().rect_filled(rect, 2.0, self.speed_color(speed));
+                let response = response.on_hover_text(format!(
+                    "{}\n{}",
+                    self.display_name(stats),
+                    format_bytes(speed, self.rate_format)
+                ));
+                if response.clicked() {
+                    clicked = Some(stats.name.clone());
+                }
+            }
+        });
+        if clicked.is_some() {
+            self.scroll_to_interface = clicked;
+        }
+    }
+
+    fn show_network_interfaces(&mut self, ui: &mut egui::Ui) {
+        // Pinned above the scroll area so it stays visible while scrolling
+        if self.quota.enabled() {
+            self.show_quota_bar(ui);
+        }
+        if self.show_total_graph {
+            self.show_total_graph(ui);
+            ui.add_space(10.0);
+        }
+        if self.show_summary {
+            let interfaces: Vec<&NetworkStats> = self
+                .visible_interfaces()
+                .into_iter()
+                .map(|(_i, s)| s)
+                .collect();
+            self.show_total_row(ui, "All interfaces", &interfaces, true);
+            ui.add_space(10.0);
+        }
+        if self.sum_selected && !self.selected_interfaces.is_empty() {
+            // Not subject to the search and filters, so the total stays whole
+            let selected: Vec<&NetworkStats> = self
+                .network_stats
+                .iter()
+                .filter(|s| self.selected_interfaces.contains(&s.name))
+                .collect();
+            self.show_total_row(ui, "Selected", &selected, false);
+            ui.add_space(10.0);
+        }
+
+        let mut events = CardEvents::default();
+        // A stable id keeps the scroll offset across refreshes
+        let scroll = egui::ScrollArea::vertical()
+            .id_salt("interfaces")
+            .show(ui, |ui| {
+                let interfaces = self.visible_interfaces();
+                let columns = if self.grid_layout {
+                    ((ui.available_width() / MIN_CARD_W).floor() as usize)
+                        .clamp(1, MAX_CARD_COLUMNS)
+                } else {
+                    1
+                };
+
+                if columns == 1 {
+                    for (_idx, stats) in interfaces {
+                        self.show_interface_card(ui, stats, &mut events);
+                        ui.add_space(10.0);
+                    }
+                } else {
+                    // Flow cards left-to-right, top-to-bottom, keeping the sort order
+                    for row in interfaces.chunks(columns) {
+                        ui.columns(columns, |cols| {
+                            for (col, (_idx, stats)) in cols.iter_mut().zip(row) {
+                                self.show_interface_card(col, stats, &mut events);
+                            }
+                        });
+                        ui.add_space(10.0);
+                    }
+                }
+            });
+        self.scroll_to_interface = None;
+        // At the very top there's nothing to preserve; stay at the top
+        self.scroll_anchor = events.scroll_anchor.filter(|_| scroll.state.offset.y > 0.0);
+        // The hovered card showed the snapshot if it already had one, so this
+        // keeps it; a newly hovered card starts one from its current values
+        self.hover_snapshot = events.hovered.filter(|_| self.freeze_on_hover);
+
+        if let Some(held) = events.toggled_freeze
+            && self.frozen.remove(&held.stats.name).is_none()
+        {
+            self.frozen.insert(held.stats.name.clone(), held);
+        }
+        if events.open_graph.is_some() {
+            self.graph_interface = events.open_graph;
+        }
+        if events.open_distribution.is_some() {
+            self.distribution_interface = events.open_distribution;
+        }
+        if let Some(name) = events.detach {
+            self.detached.insert(name);
+        }
+        for (name, collapsed) in events.collapse_changes {
+            if collapsed {
+                self.collapsed.insert(name);
+            } else {
+                self.collapsed.remove(&name);
+            }
+        }
+    }
+
+    // Larger plot of one interface's speeds over the last few minutes
+    fn show_graph_window(&mut self, ctx: &egui::Context) {
+        use egui_plot::{Legend, Line, Plot, PlotPoints};
+
+        let Some(name) = self.graph_interface.clone() else {
+            return;
+        };
+        let title = match self.network_stats.iter().find(|s| s.name == name) {
+            Some(stats) => self.display_name(stats),
+            None => "(gone)".to_string(),
+        };
+        let mut open = true;
+        egui::Window::new(format!("History: {title}"))
+            .id(egui::Id::new("graph_window"))
+            .open(&mut open)
+            .default_size([480.0, 260.0])
+            .show(ctx, |ui| {
+                if ui.button("Clear history").clicked()
+                    && let Some(history) = self.plot_histories.get_mut(&name)
+                {
+                    history.clear();
+                }
+
+                let now = Instant::now();
+                let (mut downloads, mut uploads) = (Vec::new(), Vec::new());
+                if let Some(history) = self.plot_histories.get(&name) {
+                    // Time runs left to right, ending at 0 ("now")
+                    for (ago, down, up) in history.points(now) {
+                        downloads.push([-ago, down]);
+                        uploads.push([-ago, up]);
+                    }
+                }
+
+                let rate_format = self.rate_format;
+                let palette = self.palette();
+                Plot::new("history_plot")
+                    .legend(Legend::default())
+                    .x_axis_label("seconds ago")
+                    .y_axis_label("speed")
+                    .x_axis_formatter(|mark, _range| format!("{:.0}", -mark.value))
+                    .y_axis_formatter(move |mark, _range| {
+                        format_bytes(mark.value.max(0.0), rate_format)
+                    })
+                    .include_x(-history::PLOT_SPAN.as_secs_f64())
+                    .include_x(0.0)
+                    .include_y(0.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(
+                            Line::new("Download", PlotPoints::from(downloads))
+                                .color(palette.download),
+                        );
+                        plot_ui.line(
+                            Line::new("Upload", PlotPoints::from(uploads)).color(palette.upload),
+                        );
+                    });
+            });
+        if !open {
+            self.graph_interface = None;
+        }
+    }
+
+    // Bar chart of how often one interface's download speed fell into each
+    // speed range this session
+    fn show_distribution_window(&mut self, ctx: &egui::Context) {
+        use egui_plot::{Bar, BarChart, Plot};
+
+        let Some(name) = self.distribution_interface.clone() else {
+            return;
+        };
+        let title = match self.network_stats.iter().find(|s| s.name == name) {
+            Some(stats) => self.display_name(stats),
+            None => "(gone)".to_string(),
+        };
+        // "<1 KB/s", "1 KB/s-10 KB/s", ..., ">100 MB/s"
+        let format = RateFormat {
+            number: NumberFormat {
+                precision: Some(0),
+                ..self.rate_format.number
+            },
+            ..self.rate_format
+        };
+        let edges = history::DISTRIBUTION_EDGES;
+        let labels: Vec<String> = (0..=edges.len())
+            .map(|i| match i {
+                0 => format!("<{}", format_bytes(edges[0], format)),
+                i if i == edges.len() => format!(">{}", format_bytes(edges[i - 1], format)),
+                i => format!(
+                    "{}-{}",
+                    format_bytes(edges[i - 1], format),
+                    format_bytes(edges[i], format)
+                ),
+            })
+            .collect();
+
+        let mut open = true;
+        egui::Window::new(format!("Speed distribution: {title}"))
+            .id(egui::Id::new("distribution_window"))
+            .open(&mut open)
+            .default_size([480.0, 260.0])
+            .show(ctx, |ui| {
+                let samples = self
+                    .distributions
+                    .get(&name)
+                    .map_or(0, SpeedDistribution::total);
+                ui.horizontal(|ui| {
+                    ui.label(format!("{samples} samples"));
+                    if ui.button("Clear").clicked()
+                        && let Some(distribution) = self.distributions.get_mut(&name)
+                    {
+                        distribution.clear();
+                    }
+                });
+
+                let bars: Vec<Bar> = self
+                    .distributions
+                    .get(&name)
+                    .map(|d| d.counts().to_vec())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .zip(&labels)
+                    .enumerate()
+                    .map(|(i, (count, label))| Bar::new(i as f64, count as f64).name(label))
+                    .collect();
+                let axis_labels = labels.clone();
+                Plot::new("distribution_plot")
+                    .x_axis_label("download speed")
+                    .y_axis_label("samples")
+                    .x_axis_formatter(move |mark, _range| {
+                        // Only label the bar centers
+                        let i = mark.value.round();
+                        if (mark.value - i).abs() < 1e-6 && i >= 0.0 {
+                            axis_labels.get(i as usize).cloned().unwrap_or_default()
+                        } else {
+                            String::new()
+                        }
+                    })
+                    .include_y(0.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .allow_scroll(false)
+                    .show(ui, |plot_ui| {
+                        plot_ui.bar_chart(
+                            BarChart::new("Download", bars).color(self.palette().download),
+                        );
+                    });
+            });
+        if !open {
+            self.distribution_interface = None;
+        }
+    }
+
+    // Bytes moved per interface between "Mark A" and "Mark B". Closing the
+    // window forgets both marks.
+    fn show_comparison_window(&mut self, ctx: &egui::Context) {
+        use egui::RichText;
+
+        if self.marks.iter().all(Option::is_none) {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Snapshot comparison")
+            .id(egui::Id::new("comparison_window"))
+            .open(&mut open)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                let [Some(a), Some(b)] = &self.marks else {
+                    let missing = if self.marks[0].is_none() { "A" } else { "B" };
+                    ui.label(format!("Press \"Mark {missing}\" to compare."));
+                    return;
+                };
+                let elapsed = b.taken.saturating_duration_since(a.taken);
+                ui.label(if b.taken >= a.taken {
+                    format!("B − A: {:.1} s", elapsed.as_secs_f64())
+                } else {
+                    format!(
+                        "B − A: B was marked {:.1} s before A",
+                        a.taken.duration_since(b.taken).as_secs_f64()
+                    )
+                });
+                ui.separator();
+
+                // Every interface in either snapshot, by name
+                let mut rows: BTreeMap<&str, (Option<&NetworkStats>, Option<&NetworkStats>)> =
+                    BTreeMap::new();
+                for stats in &a.stats {
+                    rows.entry(&stats.name).or_default().0 = Some(stats);
+                }
+                for stats in &b.stats {
+                    rows.entry(&stats.name).or_default().1 = Some(stats);
+                }
+                let format = self.rate_format.number;
+                egui::Grid::new("comparison_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Interface");
+                        ui.strong("Down");
+                        ui.strong("Up");
+                        ui.label("");
+                        ui.end_row();
+                        for (before, after) in rows.into_values() {
+                            let Some(either) = after.or(before) else {
+                                continue;
+                            };
+                            ui.label(self.display_name(either));
+                            match (before, after) {
+                                (Some(before), Some(after)) => {
+                                    let down =
+                                        after.bytes_received.checked_sub(before.bytes_received);
+                                    let up = after
+                                        .bytes_transmitted
+                                        .checked_sub(before.bytes_transmitted);
+                                    for delta in [down, up] {
+                                        ui.label(delta.map_or_else(
+                                            || "—".to_string(),
+                                            |delta| format_total_bytes(delta, format),
+                                        ));
+                                    }
+                                    ui.label(if down.is_none() || up.is_none() {
+                                        RichText::new("counters reset").weak()
+                                    } else {
+                                        RichText::new("")
+                                    });
+                                }
+                                (Some(_), None) => {
+                                    ui.label("—");
+                                    ui.label("—");
+                                    ui.label(RichText::new("only in A").weak());
+                                }
+                                _ => {
+                                    ui.label("—");
+                                    ui.label("—");
+                                    ui.label(RichText::new("only in B").weak());
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        if !open {
+            self.marks = [None, None];
+        }
+    }
+
+    // One small always-on-top window per detached interface. Closing a window
+    // docks the interface again; one that disappears says so until closed.
+    fn show_detached_windows(&mut self, ctx: &egui::Context) {
+        let mut docked = Vec::new();
+        for name in &self.detached {
+            let stats = self.network_stats.iter().find(|s| &s.name == name);
+            let title = stats.map_or_else(|| name.clone(), |s| self.display_name(s));
+            let body = |ui: &mut egui::Ui| match stats {
+                Some(stats) => {
+                    ui.horizontal(|ui| {
+                        for (arrow, speed) in
+                            [("⬇", stats.download_speed), ("⬆", stats.upload_speed)]
+                        {
+                            ui.label(
+                                self.speed_text(format!(
+                                    "{arrow} {}",
+                                    format_bytes(speed, self.rate_format)
+                                ))
+                                .color(self.speed_color(speed))
+                                .size(16.0)
+                                .strong(),
+                            );
+                        }
+                    });
+                }
+                None => {
+                    ui.label(egui::RichText::new("Interface no longer present").weak());
+                }
+            };
+
+            let viewport_id = egui::ViewportId::from_hash_of(("detached", name));
+            let builder = egui::ViewportBuilder::default()
+                .with_title(format!("Speedy: {title}"))
+                .with_inner_size([240.0, 60.0])
+                .with_window_level(egui::WindowLevel::AlwaysOnTop);
+            let closed = ctx.show_viewport_immediate(viewport_id, builder, |ctx, class| {
+                if class == egui::ViewportClass::Embedded {
+                    // No native multi-window support: fall back to an in-app window
+                    let mut open = true;
+                    egui::Window::new(&title)
+                        .id(egui::Id::new(viewport_id))
+                        .open(&mut open)
+                        .resizable(false)
+                        .show(ctx, body);
+                    !open
+                } else {
+                    egui::CentralPanel::default().show(ctx, body);
+                    ctx.input(|i| i.viewport().close_requested())
+                }
+            });
+            if closed {
+                docked.push(name.clone());
+            }
+        }
+        for name in docked {
+            self.detached.remove(&name);
+        }
+    }
+
+    fn show_quota_bar(&self, ui: &mut egui::Ui) {
+        let fraction = self.quota.fraction_used();
+        let since = self
+            .quota
+            .cycle_start
+            .map_or_else(String::new, |date| format!(" since {date}"));
+        let mut bar = egui::ProgressBar::new(fraction.min(1.0) as f32).text(format!(
+            "Quota: {} of {} ({:.0}%){since}",
+            format_total_bytes(self.quota.used, self.rate_format.number),
+            format_total_bytes(self.quota.limit_bytes(), self.rate_format.number),
+            fraction * 100.0
+        ));
+        if fraction >= 1.0 {
+            bar = bar.fill(egui::Color32::from_rgb(200, 50, 50));
+        } else if fraction >= 0.8 {
+            bar = bar.fill(egui::Color32::from_rgb(220, 160, 0));
+        }
+        ui.add(bar)
+            .on_hover_text("Counted only while speedy is running; the real usage may be higher");
+        ui.add_space(10.0);
+    }
+
+    // Combined download (above zero) and upload (mirrored below) of all
+    // interfaces. The y axis is symmetric and scales to the largest speed in view.
+    fn show_total_graph(&self, ui: &mut egui::Ui) {
+        use egui_plot::{Line, Plot, PlotPoints};
+
+        let now = Instant::now();
+        let (mut downloads, mut uploads) = (Vec::new(), Vec::new());
+        let mut max: f64 = 0.0;
+        for (ago, down, up) in self.total_history.points(now) {
+            downloads.push([-ago, down]);
+            uploads.push([-ago, -up]);
+            max = max.max(down).max(up);
+        }
+        // Keep an empty graph from collapsing to a zero-height range
+        let max = if max > 0.0 { max } else { 1024.0 };
+
+        let rate_format = self.rate_format;
+        let palette = self.palette();
+        Plot::new("total_plot")
+            .height(120.0)
+            .show_axes([false, true])
+            .y_axis_formatter(move |mark, _range| format_bytes(mark.value.abs(), rate_format))
+            .include_x(-history::PLOT_SPAN.as_secs_f64())
+            .include_x(0.0)
+            .include_y(max)
+            .include_y(-max)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .show(ui, |plot_ui| {
+                plot_ui.line(
+                    Line::new("Download", PlotPoints::from(downloads))
+                        .color(palette.download)
+                        .fill(0.0),
+                );
+                plot_ui.line(
+                    Line::new("Upload", PlotPoints::from(uploads))
+                        .color(palette.upload)
+                        .fill(0.0),
+                );
+            });
+    }
+
+    // Combined speeds and lifetime totals of a group of interfaces
+    // `averages` adds the combined download speed averaged over 5 s and 1 min
+    fn show_total_row(
+        &self,
+        ui: &mut egui::Ui,
+        title: &str,
+        interfaces: &[&NetworkStats],
+        averages: bool,
+    ) {
+        use egui::RichText;
+
+        let (mut download, mut upload) = (0.0, 0.0);
+        let (mut received, mut transmitted) = (0u64, 0u64);
+        for stats in interfaces {
+            download += stats.download_speed;
+            upload += stats.upload_speed;
+            received = received.saturating_add(stats.bytes_received);
+            transmitted = transmitted.saturating_add(stats.bytes_transmitted);
+        }
+
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!("{title} ({})", interfaces.len()))
+                        .size(16.0)
+                        .strong(),
+                );
+                let mut speeds = [
+                    ("Down", self.palette().download, download),
+                    ("Up", self.palette().upload, upload),
+                ];
+                if self.upload_first {
+                    speeds.reverse();
+                }
+                for (label, color, speed) in speeds {
+                    ui.add_space(10.0);
+                    ui.label(RichText::new(label).color(color));
+                    ui.label(
+                        self.speed_text(format_bytes(speed, self.rate_format))
+                            .color(self.speed_color(speed))
+                            .strong(),
+                    );
+                }
+                if averages {
+                    let now = Instant::now();
+                    // Summed per interface so the averages cover the same
+                    // interfaces as the current speed
+                    let mean = |window| {
+                        interfaces
+                            .iter()
+                            .map(|stats| {
+                                self.plot_histories
+                                    .get(&stats.name)
+                                    .and_then(|history| history.mean_download(now, window))
+                            })
+                            .sum::<Option<f64>>()
+                            .map_or_else(
+                                || "—".to_string(),
+                                |speed| format_bytes(speed, self.rate_format),
+                            )
+                    };
+                    ui.add_space(10.0);
+                    ui.label(
+                        self.speed_text(format!(
+                            "↓ {} / {} / {}",
+                            format_bytes(download, self.rate_format),
+                            mean(Duration::from_secs(5)),
+                            mean(Duration::from_secs(60))
+                        ))
+                        .weak(),
+                    )
+                    .on_hover_text(
+                        "Download now / 5 s average / 1 min average, like a load average.\n\
+                         All three cover the interfaces listed; \"—\" until each has \
+                         enough history.",
+                    );
+                }
+
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let down = format!(
+                        "Down:{}",
+                        format_total_bytes(received, self.rate_format.number)
+                    );
+                    let up = format!(
+                        "Up:{}",
+                        format_total_bytes(transmitted, self.rate_format.number)
+                    );
+                    ui.label(if self.upload_first {
+                        format!("Total: {up} {down}")
+                    } else {
+                        format!("Total: {down} {up}")
+                    })
+                    .on_hover_text(exact_totals_hint(received, transmitted));
+                });
+            });
+        });
+    }
+
+    // Small dot that lights up when a refresh sees traffic and fades back to
+    // the idle color over a short time
+    fn draw_activity_light(&self, ui: &mut egui::Ui, name: &str) {
+        const FADE: Duration = Duration::from_millis(800);
+        let (rect, _response) =
+            ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+        let palette = self.palette();
+        let glow = self
+            .last_traffic
+            .get(name)
+            .map_or(0.0, |seen| {
+                1.0 - seen.elapsed().as_secs_f32() / FADE.as_secs_f32()
+            })
+            .clamp(0.0, 1.0);
+        // Keep repainting until the fade is over, in four steps so steady
+        // traffic doesn't keep the app repainting many times a second
+        if glow > 0.0 {
+            ui.ctx().request_repaint_after(FADE / 4);
+        }
+        let color = palette.idle.lerp_to_gamma(palette.fast, glow);
+        ui.painter().circle_filled(rect.center(), 4.0, color);
+    }
+
+    fn show_interface_card(
+        &self,
+        ui: &mut egui::Ui,
+        live_stats: &NetworkStats,
+        events: &mut CardEvents,
+    ) {
+        use egui::{Color32, RichText};
+
+        // Frozen rows keep showing the values captured when they were clicked
+        let frozen = self.frozen.get(&live_stats.name);
+        let hovered = self
+            .hover_snapshot
+            .as_ref()
+            .filter(|held| held.stats.name == live_stats.name);
+        let live_units = self
+            .rate_units
+            .get(&live_stats.name)
+            .copied()
+            .unwrap_or_default();
+        // Held values keep the units they were shown in, not the live ones
+        let (stats, (down_unit, up_unit)) = match frozen.or(hovered) {
+            Some(held) => (&held.stats, held.units),
+            None => (live_stats, live_units),
+        };
+        let (down_peak, up_peak) = self.peaks.get(&stats.name).copied().unwrap_or_default();
+        let (down_avg, up_avg) = self
+            .sessions
+            .get(&stats.name)
+            .map(SessionTotals::average_speeds)
+            .unwrap_or_default();
+        // Collapsed state lives in egui's memory; the saved set only seeds it
+        let collapse_id = egui::Id::new(("interface_card", &stats.name));
+        let default_open = !self.collapsed.contains(&stats.name);
+        // Sustained uploads get a red outline
+        let mut frame = egui::Frame::group(ui.style());
+        if self.upload_flagged.contains(&stats.name) {
+            frame = frame.stroke(egui::Stroke::new(2.0, Color32::from_rgb(220, 50, 50)));
+        }
+        // The click sense is registered before the card's contents, so its
+        // buttons and collapse toggle still get their own clicks
+        let card = ui.scope_builder(egui::UiBuilder::new().sense(egui::Sense::click()), |ui| {
+            frame.show(ui, |ui| {
+                let state = egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    collapse_id,
+                    default_open,
+                );
+                let open = state.is_open();
+                let header = state.show_header(ui, |ui| {
+                    self.draw_activity_light(ui, &stats.name);
+                    if let Some((icon, kind)) = interface_icon(classify_interface(&stats.name)) {
+                        ui.label(RichText::new(icon).size(16.0))
+                            .on_hover_text(format!("{kind} (guessed from the name)"));
+                    }
+                    // Interface name (dimmed once the interface has gone idle)
+                    let name = RichText::new(self.display_name(stats)).size(16.0);
+                    let name_response = ui.label(if stats.is_active {
+                        name.strong()
+                    } else {
+                        name.weak()
+                    });
+                    let mut details = Vec::new();
+                    if !self.privacy_mode {
+                        let mac = stats
+                            .mac_address
+                            .map_or_else(|| "—".to_string(), |mac| mac.to_string());
+                        details.push(stats.name.clone());
+                        details.push(format!("MAC: {mac}"));
+                    }
+                    if let Some(mtu) = stats.mtu {
+                        details.push(format!("MTU: {mtu}"));
+                    }
+                    if let Some(mbps) = stats.link_speed_mbps {
+                        details.push(format!("Link speed: {mbps} Mbps"));
+                    }
+                    if !details.is_empty() {
+                        name_response.on_hover_text(details.join("\n"));
+                    }
+                    // First IPv4 and IPv6 address, if any
+                    if !self.privacy_mode {
+                        let ipv4 = stats.ip_addresses.iter().find(|ip| ip.is_ipv4());
+                        let ipv6 = stats.ip_addresses.iter().find(|ip| ip.is_ipv6());
+                        for ip in ipv4.into_iter().chain(ipv6) {
+                            ui.label(RichText::new(ip.to_string()).small().weak());
+                        }
+                    }
+                    if stats.packets.has_problems() {
+                        let p = &stats.packets;
+                        ui.label(RichText::new("⚠").color(Color32::from_rgb(220, 140, 0)))
+                            .on_hover_text(format!(
+                                "Receive: {} packets, {} errors, {} dropped\n\
+                             Transmit: {} packets, {} errors, {} dropped",
+                                p.rx_packets,
+                                p.rx_errs,
+                                p.rx_drop,
+                                p.tx_packets,
+                                p.tx_errs,
+                                p.tx_drop
+                            ));
+                    }
+                    if stats.is_default {
+                        ui.label(RichText::new("★ default").color(Color32::from_rgb(200, 170, 40)))
+                            .on_hover_text("Carries the default route");
+                    }
+                    if frozen.is_some() {
+                        ui.label(RichText::new("❄ frozen").color(Color32::from_rgb(80, 160, 220)));
+                    }
+                    if self.upload_flagged.contains(&stats.name) {
+                        ui.label(
+                            RichText::new("⚠ sustained upload")
+                                .color(Color32::from_rgb(220, 50, 50)),
+                        )
+                        .on_hover_text(format!(
+                            "Uploading above {} for over {} s",
+                            format_bytes(self.upload_watch_threshold, self.rate_format),
+                            self.upload_watch_for.as_secs()
+                        ));
+                    }
+                    let (ratio_text, ratio_color) = upload_ratio(stats);
+                    let ratio = RichText::new(format!("⬆/⬇ {ratio_text}")).small();
+                    ui.label(match ratio_color {
+                        Some(color) => ratio.color(color),
+                        None => ratio.weak(),
+                    })
+                    .on_hover_text("Upload speed divided by download speed");
+                    if ui
+                        .small_button("📈")
+                        .on_hover_text("Show history graph")
+                        .clicked()
+                    {
+                        events.open_graph = Some(stats.name.clone());
+                    }
+                    if ui
+                        .small_button("📊")
+                        .on_hover_text("Show distribution of download speeds")
+                        .clicked()
+                    {
+                        events.open_distribution = Some(stats.name.clone());
+                    }
+                    if ui
+                        .small_button("🗖")
+                        .on_hover_text("Detach into its own window")
+                        .clicked()
+                    {
+                        events.detach = Some(stats.name.clone());
+                    }
+
+                    // Collapsed cards still show the current speeds
+                    if !open {
+                        let mut speeds = [("⬇", stats.download_speed), ("⬆", stats.upload_speed)];
+                        if self.upload_first {
+                            speeds.reverse();
+                        }
+                        for (arrow, speed) in speeds {
+                            ui.label(
+                                self.speed_text(format!(
+                                    "{arrow} {}",
+                                    format_bytes(speed, self.rate_format)
+                                ))
+                                .color(self.speed_color(speed))
+                                .strong(),
+                            );
+                        }
+                    }
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        let font = egui::TextStyle::Body.resolve(ui.style());
+                        let text_color = ui.visuals().text_color();
+                        let mut job = egui::text::LayoutJob::default();
+                        let mut append = |text: &str, color: Color32| {
+                            job.append(text, 0.0, egui::TextFormat::simple(font.clone(), color));
+                        };
+                        append("Total: Down:", text_color);
+                        append(
+                            &format_total_bytes(stats.bytes_received, self.rate_format.number),
+                            self.total_color(stats.bytes_received).unwrap_or(text_color),
+                        );
+                        append(" Up:", text_color);
+                        append(
+                            &format_total_bytes(stats.bytes_transmitted, self.rate_format.number),
+                            self.total_color(stats.bytes_transmitted)
+                                .unwrap_or(text_color),
+                        );
+                        ui.label(job).on_hover_text(exact_totals_hint(
+                            stats.bytes_received,
+                            stats.bytes_transmitted,
+                        ));
+                    });
+                });
+                header.body(|ui| {
+                    ui.separator();
+
+                    // Speed display. Colors follow the metric, whichever side it's on.
+                    let mut groups = [
+                        (
+                            "Download",
+                            self.palette().download,
+                            stats.download_speed,
+                            down_unit,
+                            down_peak,
+                            down_avg,
+                        ),
+                        (
+                            "Upload",
+                            self.palette().upload,
+                            stats.upload_speed,
+                            up_unit,
+                            up_peak,
+                            up_avg,
+                        ),
+                    ];
+                    if self.upload_first {
+                        groups.reverse();
+                    }
+                    ui.horizontal(|ui| {
+                        for (i, (label, color, speed, unit, peak, avg)) in
+                            groups.into_iter().enumerate()
+                        {
+                            if i > 0 {
+                                ui.add_space(20.0);
+                            }
+                            ui.group(|ui| {
+                                ui.vertical(|ui| {
+                                    ui.label(RichText::new(label).color(color));
+                                    let speed_text =
+                                        format_bytes_with_unit(speed, unit, self.rate_format);
+                                    // Ensure a minimum width so values align between download/upload
+                                    const SPEED_MIN_W: f32 = 110.0;
+                                    const SPEED_H: f32 = 28.0;
+                                    ui.add_sized(
+                                        [SPEED_MIN_W, SPEED_H],
+                                        egui::Label::new(
+                                            self.speed_text(speed_text)
+                                                .color(self.speed_color(speed))
+                                                .size(18.0)
+                                                .strong(),
+                                        ),
+                                    )
+                                    .on_hover_text(self.speed_color_hint());
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "peak: {}  avg: {}",
+                                            format_bytes(peak, self.rate_format),
+                                            format_bytes(avg, self.rate_format)
+                                        ))
+                                        .small()
+                                        .weak(),
+                                    );
+                                });
+                            });
+                        }
+                    });
+
+                    // Share of the negotiated link capacity used by downloads
+                    if let Some(mbps) = stats.link_speed_mbps {
+                        let capacity = mbps as f64 * 1_000_000.0 / 8.0; // bytes per second
+                        let fraction = (stats.download_speed / capacity).clamp(0.0, 1.0) as f32;
+                        ui.add(
+                            egui::ProgressBar::new(fraction).desired_height(12.0).text(
+                                RichText::new(format!(
+                                    "{:.0}% of {mbps} Mbps link",
+                                    fraction * 100.0
+                                ))
+                                .small(),
+                            ),
+                        );
+                    }
+
+                    if let Some((tcp, udp)) = stats.connections {
+                        ui.label(
+                            RichText::new(format!("conns: {} (TCP:{tcp} UDP:{udp})", tcp + udp))
+                                .small()
+                                .weak(),
+                        )
+                        .on_hover_text("Established TCP connections and bound UDP sockets");
+                    }
+
+                    if self.show_deltas {
+                        ui.label(
+                            RichText::new(format!(
+                                "Last interval: Down:{} Up:{}",
+                                format_total_bytes(stats.rx_delta, self.rate_format.number),
+                                format_total_bytes(stats.tx_delta, self.rate_format.number)
+                            ))
+                            .small()
+                            .weak(),
+                        )
+                        .on_hover_text(format!("{} / {} bytes", stats.rx_delta, stats.tx_delta));
+                    }
+
+                    if let Some(session) = self.sessions.get(&stats.name) {
+                        ui.label(
+                            RichText::new(format!(
+                                "Session: Down:{} Up:{}",
+                                format_total_bytes(session.received, self.rate_format.number),
+                                format_total_bytes(session.transmitted, self.rate_format.number)
+                            ))
+                            .small()
+                            .weak(),
+                        );
+                    }
+
+                    if let Some(history) = self.histories.get(&stats.name) {
+                        draw_sparkline(
+                            ui,
+                            history,
+                            self.sparkline_span,
+                            self.palette(),
+                            self.show_sparkline_average,
+                        );
+                    }
+                });
+            })
+        });
+        let now_open = egui::collapsing_header::CollapsingState::load(ui.ctx(), collapse_id)
+            .map_or(default_open, |state| state.is_open());
+        if now_open != default_open {
+            events
+                .collapse_changes
+                .push((stats.name.clone(), !now_open));
+        }
+        if self.scroll_to_interface.as_ref() == Some(&stats.name) {
+            card.response.scroll_to_me(Some(egui::Align::TOP));
+        }
+        if events.scroll_anchor.is_none() && card.response.rect.bottom() > ui.clip_rect().top() {
+            events.scroll_anchor = Some(stats.name.clone());
+        }
+        if card.response.contains_pointer() {
+            events.hovered = Some(HeldStats {
+                stats: stats.clone(),
+                units: (down_unit, up_unit),
+            });
+        }
+        if card
+            .response
+            .on_hover_text("Click to freeze/unfreeze this interface's values")
+            .clicked()
+        {
+            events.toggled_freeze = Some(HeldStats {
+                stats: live_stats.clone(),
+                units: live_units,
+            });
+        }
+    }
+}
+
+// Bytes moved on one interface this session, and the counters last seen
+struct SessionTotals {
+    received: u64,
+    transmitted: u64,
+    last_received: u64,
+    last_transmitted: u64,
+    started: Instant, // when counting began: first seen or last reset
+}
+
+impl SessionTotals {
+    // Mean (download, upload) speed over the whole session, in bytes per second
+    fn average_speeds(&self) -> (f64, f64) {
+        let secs = self.started.elapsed().as_secs_f64();
+        if secs <= 0.0 {
+            return (0.0, 0.0);
+        }
+        (self.received as f64 / secs, self.transmitted as f64 / secs)
+    }
+}
+
+// Every interface's stats at one moment, kept by "Mark A"/"Mark B"
+struct CounterSnapshot {
+    taken: Instant,
+    stats: Vec<NetworkStats>,
+}
+
+// Requests from interface cards, applied once the whole list has been drawn
+#[derive(Default)]
+struct CardEvents {
+    toggled_freeze: Option<HeldStats>,
+    open_graph: Option<String>,
+    open_distribution: Option<String>,
+    detach: Option<String>,
+    collapse_changes: Vec<(String, bool)>, // (interface, now collapsed)
+    scroll_anchor: Option<String>,         // topmost card currently in view
+    hovered: Option<HeldStats>,            // values shown by the card under the pointer
+}
+
+// A card's values held while frozen or hovered, with the (download, upload)
+// rate units they were shown in
+struct HeldStats {
+    stats: NetworkStats,
+    units: (usize, usize),
+}
+
+// Glyph and description shown before an interface's name
+fn interface_icon(kind: InterfaceKind) -> Option<(&'static str, &'static str)> {
+    match kind {
+        InterfaceKind::Loopback => Some(("⟲", "Loopback")),
+        InterfaceKind::Virtual => Some(("📦", "Virtual")),
+        InterfaceKind::Wifi => Some(("📶", "Wi-Fi")),
+        InterfaceKind::Wired => Some(("🖧", "Wired Ethernet")),
+        InterfaceKind::Other => None,
+    }
+}
+
+// Tooltip for the "Total:" labels
+fn exact_totals_hint(received: u64, transmitted: u64) -> String {
+    format!(
+        "Received: {}\nSent: {}",
+        format_exact_bytes(received),
+        format_exact_bytes(transmitted)
+    )
+}
+
+// Total order on speeds for sorting, with NaN below everything else
+fn compare_speeds(a: f64, b: f64) -> Ordering {
+    let key = |speed: f64| {
+        if speed.is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            speed
+        }
+    };
+    key(a).total_cmp(&key(b))
+}
+
+// Current upload:download speed ratio as text, with a warning color when
+// uploads clearly dominate (e.g. seeding). "—" when nothing is downloading.
+fn upload_ratio(stats: &NetworkStats) -> (String, Option<egui::Color32>) {
+    if stats.download_speed <= 0.0 {
+        return ("—".to_string(), None);
+    }
+    let ratio = stats.upload_speed / stats.download_speed;
+    let color = if ratio >= 10.0 {
+        Some(egui::Color32::from_rgb(220, 50, 50))
+    } else if ratio >= 2.0 {
+        Some(egui::Color32::from_rgb(220, 140, 0))
+    } else {
+        None
+    };
+    (format!("{ratio:.1}"), color)
+}
+
+// Whole seconds as "1h 02m 03s", "2m 05s" or "5s"
+fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{hours}h {minutes:02}m {seconds:02}s")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds:02}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+// Draw the last `span` of download/upload history as two lines scaled to the
+// larger series, with "now" at the right edge. With `show_average`, a lighter
+// moving-average line is overlaid on each.
+fn draw_sparkline(
+    ui: &mut egui::Ui,
+    history: &TimedHistory,
+    span: Duration,
+    palette: &Palette,
+    show_average: bool,
+) {
+    use egui::Stroke;
+
+    const SPARKLINE_H: f32 = 32.0;
+    let (rect, _response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), SPARKLINE_H),
+        egui::Sense::hover(),
+    );
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    // Samples from before a shortened span are left out
+    let span_secs = span.as_secs_f64();
+    let (mut ages, mut downloads, mut uploads) = (Vec::new(), Vec::new(), Vec::new());
+    for (ago, down, up) in history.points(Instant::now()) {
+        if ago <= span_secs {
+            ages.push(ago);
+            downloads.push(down);
+            uploads.push(up);
+        }
+    }
+    let max = downloads
+        .iter()
+        .chain(&uploads)
+        .copied()
+        .fold(0.0_f64, f64::max);
+    let to_points = |values: &[f64]| -> Vec<egui::Pos2> {
+        ages.iter()
+            .zip(values)
+            .map(|(ago, v)| {
+                let y = if max > 0.0 { (v / max) as f32 } else { 0.0 };
+                egui::pos2(
+                    rect.right() - (ago / span_secs) as f32 * rect.width(),
+                    rect.bottom() - y * rect.height(),
+                )
+            })
+            .collect()
+    };
+
+    let painter = ui.painter_at(rect);
+    for (values, color) in [(&downloads, palette.download), (&uploads, palette.upload)] {
+        painter.add(egui::Shape::line(
+            to_points(values),
+            Stroke::new(1.5, color),
+        ));
+        if show_average {
+            let average = history::moving_average(values, history::AVERAGE_WINDOW);
+            painter.add(egui::Shape::line(
+                to_points(&average),
+                Stroke::new(3.0, color.gamma_multiply(0.35)),
+            ));
+        }
+    }
+}
+
+fn main() -> Result<(), eframe::Error> {
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    // Metrics stay on localhost unless another address is asked for
+    let metrics_bind = args
+        .metrics_bind
+        .unwrap_or(std::net::Ipv4Addr::LOCALHOST.into());
+    let metrics = match args
+        .metrics_port
+        .map(|port| metrics::serve(metrics_bind, port))
+        .transpose()
+    {
+        Ok(metrics) => metrics,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+    // --interface flags win over the environment variable
+    let interfaces = if args.interfaces.is_empty() {
+        cli::interfaces_from_env()
+    } else {
+        args.interfaces.clone()
+    };
+    // Note: release builds on Windows have no console attached, so run
+    // headless mode from a debug build or redirect stdout to a file there
+    if args.headless {
+        headless::run(
+            args.interval.unwrap_or(Duration::from_secs(1)),
+            metrics,
+            interfaces,
+        );
+        return Ok(());
+    }
+
+    // Keep settings in a user-chosen folder, e.g. for portable installs
+    if let Some(dir) = &args.data_dir
+        && let Err(err) = std::fs::create_dir_all(dir)
+    {
+        eprintln!("Failed to create data directory {}: {err}", dir.display());
+    }
+
+    let config_path = settings::config_path(args.data_dir.as_deref());
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size(INITIAL_WINDOW_SIZE)
+            .with_min_inner_size(MIN_WINDOW_SIZE)
+            // The window level is applied on the first frame from the saved setting
+            .with_icon(eframe::icon_data::from_png_bytes(&[]).unwrap_or_default()),
+        persistence_path: args.data_dir.as_ref().map(|dir| dir.join("speedy.ron")),
+        // Geometry is restored by the app itself, with an off-screen check
+        persist_window: false,
+        ..Default::default()
+    };
+
+    eframe::run_native(
+        "Speedy - Network Speed Monitor",
+        options,
+        Box::new(|cc| {
+            // Load Chinese fonts for better character support
+            #[cfg(target_os = "windows")]
+            let mut fonts = egui::FontDefinitions::default();
+            #[cfg(not(target_os = "windows"))]
+            let fonts = egui::FontDefinitions::default();
+
+            // Add system fonts that support Chinese characters
+            #[cfg(target_os = "windows")]
+            {
+                // Try to load system fonts that support Chinese
+                if let Ok(font_data) = std::fs::read("C:\\Windows\\Fonts\\msyh.ttc") {
+                    fonts.font_data.insert(
+                        "Microsoft YaHei".to_owned(),
+                        egui::FontData::from_owned(font_data).into(),
+                    );
+
+                    // Set as primary font for better Chinese support
+                    fonts
+                        .families
+                        .get_mut(&egui::FontFamily::Proportional)
+                        .unwrap()
+                        .insert(0, "Microsoft YaHei".to_owned());
+                } else if let Ok(font_data) = std::fs::read("C:\\Windows\\Fonts\\simhei.ttf") {
+                    fonts.font_data.insert(
+                        "SimHei".to_owned(),
+                        egui::FontData::from_owned(font_data).into(),
+                    );
+
+                    fonts
+                        .families
+                        .get_mut(&egui::FontFamily::Proportional)
+                        .unwrap()
+                        .insert(0, "SimHei".to_owned());
+                }
+            }
+
+            cc.egui_ctx.set_fonts(fonts);
+
+            // Initialize app and restore saved settings
+            let mut app = SpeedyApp {
+                storage_available: cc.storage.is_some(),
+                metrics,
+                ..Default::default()
+            };
+            app.network_monitor.set_allowlist(interfaces);
+            if let Some(storage) = cc.storage {
+                app.load_settings(storage);
+                app.load_window_geometry(storage);
+                app.load_quota_usage(storage);
+            }
+            // config.json overrides eframe's storage where both have a setting
+            app.config_path = config_path;
+            if let Some(path) = &app.config_path
+                && let Some(config) = settings::read_config(path)
+            {
+                match config {
+                    Ok(config) => app.load_settings(&config.to_storage()),
+                    Err(err) => eprintln!("Ignoring config file: {err}"),
+                }
+                // The file is left alone until a setting is changed here
+                let mut current = MemoryStorage::default();
+                app.save_settings(&mut current);
+                app.written_config = Some(settings::Config::from_storage(&current));
+            }
+            cc.egui_ctx.set_theme(app.theme);
+            #[cfg(any(target_os = "windows", target_os = "macos"))]
+            {
+                app.tray = tray::Tray::new(&cc.egui_ctx)
+                    .inspect_err(|e| eprintln!("Failed to create tray icon: {e}"))
+                    .ok();
+            }
+            app.hotkey = hotkey::Hotkey::new(&cc.egui_ctx)
+                .inspect_err(|e| eprintln!("Global hotkeys unavailable: {e}"))
+                .ok();
+            app.apply_hotkey();
+
+            Ok(Box::new(app))
+        }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nan_speeds_sort_lowest() {
+        let mut speeds = [3.0, f64::NAN, 0.0, 1.5];
+        speeds.sort_by(|a, b| compare_speeds(*a, *b));
+        assert!(speeds[0].is_nan());
+        assert_eq!(speeds[1..], [0.0, 1.5, 3.0]);
+
+        assert_eq!(compare_speeds(f64::NAN, f64::NAN), Ordering::Equal);
+        assert_eq!(compare_speeds(f64::NAN, -1.0), Ordering::Less);
+    }
+
+    #[test]
+    fn partial_config_keeps_other_speed_threshold() {
+        let config: settings::Config =
+            serde_json::from_str(r#"{ "fast_speed": 4194304.0 }"#).unwrap();
+        let mut app = SpeedyApp::default();
+        app.load_settings(&config.to_storage());
+        assert_eq!(app.fast_speed, 4.0 * 1024.0 * 1024.0);
+        assert_eq!(app.medium_speed, DEFAULT_MEDIUM_SPEED);
+
+        // Still rejected when it would end up at or below medium
+        let config: settings::Config = serde_json::from_str(r#"{ "fast_speed": 1.0 }"#).unwrap();
+        app.load_settings(&config.to_storage());
+        assert_eq!(app.fast_speed, 4.0 * 1024.0 * 1024.0);
+    }
+}
@@ -0,0 +1,95 @@
+// Prometheus metrics endpoint serving the latest stats
+use crate::network_monitor::NetworkStats;
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+// Latest stats, written by whoever runs the monitor and read by the server
+pub type Snapshot = Arc<Mutex<Vec<NetworkStats>>>;
+
+// Start serving `/metrics` at `address`:`port` on a background thread
+pub fn serve(address: IpAddr, port: u16) -> Result<Snapshot, String> {
+    let server = tiny_http::Server::http((address, port))
+        .map_err(|e| format!("Failed to start metrics server on {address} port {port}: {e}"))?;
+    let snapshot = Snapshot::default();
+    let shared = Arc::clone(&snapshot);
+    std::thread::spawn(move || {
+        for request in server.incoming_requests() {
+            let response = if request.url() == "/metrics" {
+                let body = render(&shared.lock().unwrap_or_else(|e| e.into_inner()));
+                let content_type = tiny_http::Header::from_bytes(
+                    "Content-Type",
+                    "text/plain; version=0.0.4; charset=utf-8",
+                )
+                .expect("static header is valid");
+                tiny_http::Response::from_string(body).with_header(content_type)
+            } else {
+                tiny_http::Response::from_string("Not found").with_status_code(404)
+            };
+            let _ = request.respond(response);
+        }
+    });
+    Ok(snapshot)
+}
+
+pub fn update(snapshot: &Snapshot, stats: &[NetworkStats]) {
+    *snapshot.lock().unwrap_or_else(|e| e.into_inner()) = stats.to_vec();
+}
+
+// (name, type, help, value) of a metric family
+type Family = (
+    &'static str,
+    &'static str,
+    &'static str,
+    fn(&NetworkStats) -> f64,
+);
+
+// Text exposition format, one family per metric
+fn render(stats: &[NetworkStats]) -> String {
+    let families: [Family; 4] = [
+        (
+            "speedy_download_bytes_per_second",
+            "gauge",
+            "Current download speed",
+            |s| s.raw_download_speed,
+        ),
+        (
+            "speedy_upload_bytes_per_second",
+            "gauge",
+            "Current upload speed",
+            |s| s.raw_upload_speed,
+        ),
+        ("speedy_rx_bytes_total", "counter", "Bytes received", |s| {
+            s.bytes_received as f64
+        }),
+        (
+            "speedy_tx_bytes_total",
+            "counter",
+            "Bytes transmitted",
+            |s| s.bytes_transmitted as f64,
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, kind, help, value) in families {
+        let _ = writeln!(out, "# HELP {name} {help}.");
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+        for s in stats {
+            let _ = writeln!(
+                out,
+                "{name}{{interface=\"{}\"}} {}",
+                escape_label(&s.name),
+                value(s)
+            );
+        }
+    }
+    out
+}
+
+// Label values escape backslash, double quote and newline
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,153 @@
+// Linux-specific counter sources that bypass sysinfo
+use std::path::Path;
+
+// Read an interface's byte counters from sysfs
+// (`/sys/class/net/<iface>/statistics/{rx,tx}_bytes`). Returns `None` if
+// either file is missing or unreadable, so callers can fall back per interface.
+pub fn read_sysfs_counters(interface: &str) -> Option<(u64, u64)> {
+    let statistics = Path::new("/sys/class/net")
+        .join(interface)
+        .join("statistics");
+    let read = |file: &str| -> Option<u64> {
+        std::fs::read_to_string(statistics.join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    Some((read("rx_bytes")?, read("tx_bytes")?))
+}
+
+// MTU and negotiated link speed in Mbps from `/sys/class/net/<iface>/{mtu,speed}`.
+// Wireless and virtual interfaces have no speed (the read fails or gives -1),
+// and interfaces that are down fail the read too.
+pub fn read_link_info(interface: &str) -> (Option<u32>, Option<u32>) {
+    let dir = Path::new("/sys/class/net").join(interface);
+    let read = |file: &str| -> Option<i64> {
+        std::fs::read_to_string(dir.join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let positive = |value: i64| u32::try_from(value).ok().filter(|v| *v > 0);
+    (
+        read("mtu").and_then(positive),
+        read("speed").and_then(positive),
+    )
+}
+
+#[derive(Debug, Clone)]
+pub struct LinuxNetworkStats {
+    pub name: String,
+    pub bytes_received: u64,
+    pub bytes_transmitted: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errs: u64,
+    pub rx_drop: u64,
+    pub tx_errs: u64,
+    pub tx_drop: u64,
+}
+
+pub const PROC_NET_DEV: &str = "/proc/net/dev";
+
+// Read byte counters for all interfaces from a `/proc/net/dev` style file
+// (normally `PROC_NET_DEV`). Fails if the file can't be read, e.g. in
+// containers without `/proc` mounted.
+pub fn read_proc_net_dev(
+    path: impl AsRef<Path>,
+) -> Result<Vec<LinuxNetworkStats>, Box<dyn std::error::Error>> {
+    parse_proc_net_dev(&std::fs::read_to_string(path)?)
+}
+
+// Parse the contents of a `/proc/net/dev` style file. Long counters can run
+// into the name with no space in between (`eth0:123`), so split on the colon.
+pub fn parse_proc_net_dev(
+    contents: &str,
+) -> Result<Vec<LinuxNetworkStats>, Box<dyn std::error::Error>> {
+    let mut stats = Vec::new();
+
+    // The first two lines are column headers
+    for line in contents.lines().skip(2) {
+        let Some((name, counters)) = line.split_once(':') else {
+            continue;
+        };
+        let fields: Vec<&str> = counters.split_whitespace().collect();
+        if fields.len() < 12 {
+            continue;
+        }
+        // 8 receive columns (bytes packets errs drop fifo frame compressed
+        // multicast) followed by transmit columns (bytes packets errs drop ...)
+        stats.push(LinuxNetworkStats {
+            name: name.trim().to_string(),
+            bytes_received: fields[0].parse()?,
+            rx_packets: fields[1].parse()?,
+            rx_errs: fields[2].parse()?,
+            rx_drop: fields[3].parse()?,
+            bytes_transmitted: fields[8].parse()?,
+            tx_packets: fields[9].parse()?,
+            tx_errs: fields[10].parse()?,
+            tx_drop: fields[11].parse()?,
+        });
+    }
+
+    Ok(stats)
+}
+
+// Interface carrying the IPv4 default route, from `/proc/net/route`. With
+// several default routes, the one with the lowest metric wins.
+pub fn default_route_interface() -> Option<String> {
+    let contents = std::fs::read_to_string("/proc/net/route").ok()?;
+    contents
+        .lines()
+        .skip(1) // column headers
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // Iface Destination Gateway Flags RefCnt Use Metric Mask ...
+            if fields.len() < 8 || fields[1] != "00000000" || fields[7] != "00000000" {
+                return None;
+            }
+            let metric: u32 = fields[6].parse().ok()?;
+            Some((metric, fields[0].to_string()))
+        })
+        .min()
+        .map(|(_metric, name)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:    2776      32    0    0    0     0          0         0     2776      32    0    0    0     0       0          0
+  eth0:123 456 1 2 0 0 0 0 789 1011 3 4 0 0 0 0
+";
+
+    #[test]
+    fn parses_proc_net_dev() {
+        let stats = parse_proc_net_dev(SAMPLE).unwrap();
+        assert_eq!(stats.len(), 2);
+
+        assert_eq!(stats[0].name, "lo");
+        assert_eq!(stats[0].bytes_received, 2776);
+        assert_eq!(stats[0].bytes_transmitted, 2776);
+
+        // No space between the name and the first counter
+        let eth0 = &stats[1];
+        assert_eq!(eth0.name, "eth0");
+        assert_eq!(eth0.bytes_received, 123);
+        assert_eq!(eth0.rx_packets, 456);
+        assert_eq!((eth0.rx_errs, eth0.rx_drop), (1, 2));
+        assert_eq!(eth0.bytes_transmitted, 789);
+        assert_eq!(eth0.tx_packets, 1011);
+        assert_eq!((eth0.tx_errs, eth0.tx_drop), (3, 4));
+    }
+
+    #[test]
+    fn missing_file_is_an_error() {
+        assert!(read_proc_net_dev("/nonexistent/speedy/net/dev").is_err());
+    }
+}
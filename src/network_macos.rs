@@ -0,0 +1,71 @@
+// macOS-specific interface naming and filtering
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::OnceLock;
+
+// BSD device name -> hardware port name (e.g. "en0" -> "Wi-Fi"), as reported
+// by `networksetup -listallhardwareports`. Read once; empty if unavailable.
+fn hardware_ports() -> &'static HashMap<String, String> {
+    static PORTS: OnceLock<HashMap<String, String>> = OnceLock::new();
+    PORTS.get_or_init(|| {
+        let Ok(output) = Command::new("networksetup")
+            .arg("-listallhardwareports")
+            .output()
+        else {
+            return HashMap::new();
+        };
+        parse_hardware_ports(&String::from_utf8_lossy(&output.stdout))
+    })
+}
+
+// Parses blocks of the form:
+//   Hardware Port: Wi-Fi
+//   Device: en0
+fn parse_hardware_ports(output: &str) -> HashMap<String, String> {
+    let mut ports = HashMap::new();
+    let mut port = None;
+    for line in output.lines() {
+        if let Some(name) = line.strip_prefix("Hardware Port:") {
+            port = Some(name.trim().to_string());
+        } else if let Some(device) = line.strip_prefix("Device:")
+            && let Some(port) = port.take()
+        {
+            ports.insert(device.trim().to_string(), port);
+        }
+    }
+    ports
+}
+
+// Human-friendly name for a BSD interface name such as `en0`
+pub fn clean_interface_name(name: &str) -> String {
+    if let Some(port) = hardware_ports().get(name) {
+        return port.clone();
+    }
+    // Without networksetup, fall back to the usual layout: en0 is the built-in
+    // Wi-Fi on laptops, other enN interfaces are wired or adapters.
+    match name {
+        "en0" => "Wi-Fi".to_string(),
+        _ if name.starts_with("en") => "Ethernet".to_string(),
+        _ => name.to_string(),
+    }
+}
+
+// Apple's virtual interfaces: AirDrop/Wi-Fi Aware (awdl, llw), VPN and system
+// tunnels (utun) and bridges such as Thunderbolt Bridge (bridge)
+pub fn is_apple_virtual(name: &str) -> bool {
+    ["awdl", "llw", "utun", "bridge"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+// Interface carrying the default route, from `route -n get default`
+pub fn default_route_interface() -> Option<String> {
+    let output = Command::new("route")
+        .args(["-n", "get", "default"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("interface:"))
+        .map(|name| name.trim().to_string())
+}
@@ -1,120 +1,1211 @@
-use std::collections::HashMap;
-use std::time::Instant;
-use sysinfo::Networks;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use sysinfo::{MacAddr, NetworkData, Networks};
 
 #[derive(Debug, Clone)]
 pub struct NetworkStats {
-    pub name: String,
+    pub name: String,         // OS interface name; the key for all per-interface state
+    pub display_name: String, // friendlier name where the platform's names are cryptic
     pub bytes_received: u64,
     pub bytes_transmitted: u64,
-    pub download_speed: f64, // bytes per second
-    pub upload_speed: f64,   // bytes per second
+    pub download_speed: f64, // bytes per second, smoothed if smoothing is on
+    pub upload_speed: f64,   // bytes per second, smoothed if smoothing is on
+    pub raw_download_speed: f64,
+    pub raw_upload_speed: f64,
+    pub rx_delta: u64,               // bytes received since the previous refresh
+    pub tx_delta: u64,               // bytes transmitted since the previous refresh
+    pub is_active: bool,             // traffic seen within the activity grace period
+    pub idle_time: Option<Duration>, // since the last traffic; None if none seen yet
+    pub ip_addresses: Vec<IpAddr>,
+    pub mac_address: Option<MacAddr>, // None when unknown or all zeros
+    pub packets: PacketCounters,
+    pub mtu: Option<u32>,
+    pub link_speed_mbps: Option<u32>, // negotiated speed, where the platform reports it
+    pub is_default: bool,             // carries the default route
+    pub connections: Option<(usize, usize)>, // open (TCP, UDP) connections (Windows only)
+}
+
+// Lifetime packet, error and drop counters. Drops are only reported by the
+// /proc/net/dev backend; elsewhere they stay 0.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PacketCounters {
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errs: u64,
+    pub rx_drop: u64,
+    pub tx_errs: u64,
+    pub tx_drop: u64,
+}
+
+impl PacketCounters {
+    pub fn has_problems(&self) -> bool {
+        self.rx_errs + self.rx_drop + self.tx_errs + self.tx_drop > 0
+    }
+}
+
+// Where byte counters are read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Sysinfo,
+    // Per-interface sysfs statistics files (Linux only; other platforms use sysinfo)
+    Sysfs,
+    // `/proc/net/dev`, read directly without sysinfo (Linux only)
+    ProcNetDev,
+}
+
+// Address family of the connections counted per interface (Windows only)
+#[cfg(any(target_os = "windows", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AddressFamily {
+    #[default]
+    Both,
+    V4,
+    V6,
+}
+
+#[cfg(any(target_os = "windows", test))]
+impl AddressFamily {
+    fn matches(self, address: &IpAddr) -> bool {
+        match self {
+            AddressFamily::Both => true,
+            AddressFamily::V4 => address.is_ipv4(),
+            AddressFamily::V6 => address.is_ipv6(),
+        }
+    }
+}
+
+// Transport protocol of a connection (Windows only)
+#[cfg(any(target_os = "windows", test))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+// (TCP, UDP) connections of the given family belonging to an interface: a
+// connection belongs to the interface owning its local address
+#[cfg(any(target_os = "windows", test))]
+fn count_connections(
+    connections: &[(Protocol, IpAddr)],
+    addresses: &[IpAddr],
+    family: AddressFamily,
+) -> (usize, usize) {
+    let count = |protocol| {
+        connections
+            .iter()
+            .filter(|(p, address)| {
+                *p == protocol && family.matches(address) && addresses.contains(address)
+            })
+            .count()
+    };
+    (count(Protocol::Tcp), count(Protocol::Udp))
 }
 
 pub struct NetworkMonitor {
     networks: Networks,
+    backend: Backend,
     previous_stats: HashMap<String, (u64, u64, Instant)>, // interface -> (rx, tx, timestamp)
+    last_speeds: HashMap<String, (f64, f64)>,             // interface -> (download, upload)
+    last_active: HashMap<String, Instant>,                // interface -> last time traffic was seen
+    active_grace: Duration,
+    smoothing: bool,
+    smoothing_alpha: f64,
+    smoothed_speeds: HashMap<String, (f64, f64)>, // interface -> (download, upload)
+    default_route: Option<DefaultRoute>,
+    default_route_checked: Option<Instant>,
+    fallback_warning: Option<String>, // why the selected backend isn't being used
+    empty_refreshes: u32,             // consecutive refreshes that found no interfaces at all
+    last_sampled: usize,              // interfaces found by the last refresh, before filtering
+    allowlist: BTreeSet<String>,      // only report these interfaces; empty = all
+    #[cfg(target_os = "windows")]
+    connections: Vec<(Protocol, IpAddr)>, // with their local address
+    #[cfg(target_os = "windows")]
+    connections_checked: Option<Instant>,
+    #[cfg(target_os = "windows")]
+    connection_family: AddressFamily,
+}
+
+// How the interface carrying the default route is identified
+#[derive(Debug, Clone, PartialEq)]
+enum DefaultRoute {
+    #[cfg(not(target_os = "windows"))]
+    Name(String),
+    #[cfg(target_os = "windows")]
+    Address(IpAddr),
+}
+
+// Looking up the default route may spawn a process, so it's only repeated this often
+const DEFAULT_ROUTE_TTL: Duration = Duration::from_secs(10);
+
+fn lookup_default_route() -> Option<DefaultRoute> {
+    #[cfg(target_os = "linux")]
+    return crate::network_linux::default_route_interface().map(DefaultRoute::Name);
+    #[cfg(target_os = "macos")]
+    return crate::network_macos::default_route_interface().map(DefaultRoute::Name);
+    #[cfg(target_os = "windows")]
+    return crate::network_windows::default_route_address().map(DefaultRoute::Address);
+    #[allow(unreachable_code)]
+    None
 }
 
+// Connection tables can hold thousands of rows, so they're only re-read this often
+#[cfg(target_os = "windows")]
+const CONNECTIONS_TTL: Duration = Duration::from_secs(5);
+
+// How long an interface stays "active" after its last traffic by default
+pub const DEFAULT_ACTIVE_GRACE: Duration = Duration::from_secs(5);
+// Weight of the newest sample in the exponential moving average
+pub const DEFAULT_SMOOTHING_ALPHA: f64 = 0.3;
+
 impl NetworkMonitor {
-    pub fn new() -> Self {
+    pub fn new(backend: Backend) -> Self {
         // Create networks instance and refresh to get initial data
         let networks = Networks::new_with_refreshed_list();
 
-        Self {
+        let mut monitor = Self {
             networks,
+            backend,
             previous_stats: HashMap::new(),
+            last_speeds: HashMap::new(),
+            last_active: HashMap::new(),
+            active_grace: DEFAULT_ACTIVE_GRACE,
+            smoothing: false,
+            smoothing_alpha: DEFAULT_SMOOTHING_ALPHA,
+            smoothed_speeds: HashMap::new(),
+            default_route: None,
+            default_route_checked: None,
+            fallback_warning: None,
+            empty_refreshes: 0,
+            last_sampled: 0,
+            allowlist: BTreeSet::new(),
+            #[cfg(target_os = "windows")]
+            connections: Vec::new(),
+            #[cfg(target_os = "windows")]
+            connections_checked: None,
+            #[cfg(target_os = "windows")]
+            connection_family: AddressFamily::Both,
+        };
+        // Seed the baseline from this initial sample, so the first refresh
+        // already measures the delta since startup rather than reporting 0,
+        // and lifetime totals never count towards a speed.
+        monitor.seed_baseline();
+        monitor
+    }
+
+    // Restart speed measurement from the current counters, e.g. after a pause,
+    // so the next sample doesn't average over the whole gap.
+    pub fn rebaseline(&mut self) {
+        self.seed_baseline();
+        self.smoothed_speeds.clear();
+    }
+
+    fn seed_baseline(&mut self) {
+        let samples = self
+            .sample_counters()
+            .into_iter()
+            .map(|(name, rx, tx, _)| (name, rx, tx));
+        self.seed_from(samples, Instant::now());
+    }
+
+    // Start measuring from these (interface, rx, tx) counters
+    fn seed_from(&mut self, samples: impl IntoIterator<Item = (String, u64, u64)>, now: Instant) {
+        self.previous_stats = samples
+            .into_iter()
+            .map(|(name, rx, tx)| (name, (rx, tx, now)))
+            .collect();
+    }
+
+    // Current (interface, rx, tx, packets) counters from the selected backend
+    fn sample_counters(&mut self) -> Vec<(String, u64, u64, PacketCounters)> {
+        #[cfg(target_os = "linux")]
+        if self.backend == Backend::ProcNetDev {
+            match crate::network_linux::read_proc_net_dev(crate::network_linux::PROC_NET_DEV) {
+                Ok(stats) => {
+                    return stats
+                        .into_iter()
+                        .map(|s| {
+                            let packets = PacketCounters {
+                                rx_packets: s.rx_packets,
+                                tx_packets: s.tx_packets,
+                                rx_errs: s.rx_errs,
+                                rx_drop: s.rx_drop,
+                                tx_errs: s.tx_errs,
+                                tx_drop: s.tx_drop,
+                            };
+                            (s.name, s.bytes_received, s.bytes_transmitted, packets)
+                        })
+                        .collect();
+                }
+                Err(err) => {
+                    // Record the first failure only; sysinfo takes over below
+                    if self.fallback_warning.is_none() {
+                        let warning = format!(
+                            "{} unavailable ({err}), using sysinfo",
+                            crate::network_linux::PROC_NET_DEV
+                        );
+                        eprintln!("{warning}");
+                        self.fallback_warning = Some(warning);
+                    }
+                }
+            }
+        }
+
+        // sysinfo (also the fallback if /proc/net/dev can't be read)
+        self.networks.refresh(false);
+        self.networks
+            .iter()
+            .map(|(name, data)| {
+                let (rx, tx) = self.read_counters(name, data);
+                let packets = PacketCounters {
+                    rx_packets: data.total_packets_received(),
+                    tx_packets: data.total_packets_transmitted(),
+                    rx_errs: data.total_errors_on_received(),
+                    tx_errs: data.total_errors_on_transmitted(),
+                    ..Default::default()
+                };
+                (name.clone(), rx, tx, packets)
+            })
+            .collect()
+    }
+
+    pub fn active_grace(&self) -> Duration {
+        self.active_grace
+    }
+
+    // An interface stays active until it has been idle continuously for this long,
+    // so brief lulls don't make it flicker between active and inactive.
+    pub fn set_active_grace(&mut self, grace: Duration) {
+        self.active_grace = grace;
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    // Why the last refresh returned no interfaces, once that's unlikely to be
+    // just startup. `None` while interfaces are being found and shown.
+    pub fn diagnostic(&self, shown: usize) -> Option<String> {
+        // A couple of empty refreshes can happen while the system settles
+        const EMPTY_REFRESHES_BEFORE_DIAGNOSTIC: u32 = 3;
+        if self.empty_refreshes >= EMPTY_REFRESHES_BEFORE_DIAGNOSTIC {
+            Some(format!(
+                "No network interfaces found after {} refreshes. Network statistics may \
+                 not be readable here (permissions or a sandbox).",
+                self.empty_refreshes
+            ))
+        } else if shown == 0 && !self.allowlist.is_empty() {
+            let names: Vec<&str> = self.allowlist.iter().map(String::as_str).collect();
+            Some(format!(
+                "None of the requested interfaces ({}) were found, or they are loopback or \
+                 virtual and hidden by the current options.",
+                names.join(", ")
+            ))
+        } else if shown == 0 && self.last_sampled > 0 {
+            Some(format!(
+                "All {} interfaces are loopback or virtual and hidden by the current options.",
+                self.last_sampled
+            ))
+        } else {
+            None
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    pub fn connection_family(&self) -> AddressFamily {
+        self.connection_family
+    }
+
+    // Count only connections of this family; applies from the next refresh
+    #[cfg(target_os = "windows")]
+    pub fn set_connection_family(&mut self, family: AddressFamily) {
+        self.connection_family = family;
+    }
+
+    // Restrict monitoring to these interface names (from the command line).
+    // Nothing else is reported at all, whatever the UI filters say.
+    pub fn set_allowlist(&mut self, names: impl IntoIterator<Item = String>) {
+        self.allowlist = names.into_iter().collect();
+    }
+
+    // Set once if the selected backend failed and sysinfo was used instead
+    pub fn fallback_warning(&self) -> Option<&str> {
+        self.fallback_warning.as_deref()
+    }
+
+    pub fn set_backend(&mut self, backend: Backend) {
+        if backend != self.backend {
+            // Counters from different sources aren't comparable, so start over
+            self.backend = backend;
+            self.fallback_warning = None;
+            self.previous_stats.clear();
+            self.last_speeds.clear();
+            self.smoothed_speeds.clear();
         }
     }
 
+    pub fn smoothing(&self) -> bool {
+        self.smoothing
+    }
+
+    pub fn set_smoothing(&mut self, smoothing: bool) {
+        self.smoothing = smoothing;
+        self.smoothed_speeds.clear();
+    }
+
+    pub fn smoothing_alpha(&self) -> f64 {
+        self.smoothing_alpha
+    }
+
+    // Lower values smooth more; 1.0 shows the raw speeds
+    pub fn set_smoothing_alpha(&mut self, alpha: f64) {
+        self.smoothing_alpha = alpha.clamp(0.01, 1.0);
+    }
+
+    // Exponential moving average of the speeds: alpha * new + (1 - alpha) * previous.
+    // Repeated (not freshly measured) speeds leave the average unchanged.
+    fn smooth(&mut self, interface: &str, raw: (f64, f64), fresh: bool) -> (f64, f64) {
+        if !self.smoothing {
+            return raw;
+        }
+        let alpha = self.smoothing_alpha;
+        let smoothed = match self.smoothed_speeds.get(interface) {
+            Some(&previous) if !fresh => previous,
+            Some(&(download, upload)) => (
+                alpha * raw.0 + (1.0 - alpha) * download,
+                alpha * raw.1 + (1.0 - alpha) * upload,
+            ),
+            None => raw,
+        };
+        self.smoothed_speeds.insert(interface.to_string(), smoothed);
+        smoothed
+    }
+
+    // Byte counters for an interface from the selected backend, falling back
+    // to sysinfo's values for this interface if the backend can't provide them.
+    fn read_counters(&self, interface: &str, data: &NetworkData) -> (u64, u64) {
+        #[cfg(target_os = "linux")]
+        if self.backend == Backend::Sysfs
+            && let Some(counters) = crate::network_linux::read_sysfs_counters(interface)
+        {
+            return counters;
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = interface;
+
+        (data.total_received(), data.total_transmitted())
+    }
+
+    // Returns `None` when the previous sample was taken at the same instant
+    // (possible on coarse clocks), in which case no delta can be computed yet.
     fn compute_speeds(
         &self,
         interface: &str,
         current_rx: u64,
         current_tx: u64,
         current_time: Instant,
-    ) -> (f64, f64) {
+    ) -> Option<(f64, f64)> {
         if let Some((prev_rx, prev_tx, prev_time)) = self.previous_stats.get(interface) {
             let duration = current_time.duration_since(*prev_time).as_secs_f64();
-            if duration > 0.0 {
-                let download_speed = (current_rx.saturating_sub(*prev_rx) as f64) / duration;
-                let upload_speed = (current_tx.saturating_sub(*prev_tx) as f64) / duration;
-                return (download_speed, upload_speed);
+            if duration <= 0.0 {
+                return None;
             }
+            // A counter going backwards means it was reset (driver reset, 32-bit
+            // wraparound). Report 0 for that interval; the caller re-baselines
+            // on the new value.
+            let rate = |current: u64, prev: u64| {
+                current
+                    .checked_sub(prev)
+                    .map_or(0.0, |delta| delta as f64 / duration)
+            };
+            return Some((rate(current_rx, *prev_rx), rate(current_tx, *prev_tx)));
         }
-        (0.0, 0.0)
+        Some((0.0, 0.0))
     }
 
-    pub fn refresh(&mut self) -> Vec<NetworkStats> {
-        self.networks.refresh(false);
+    // Speeds and byte deltas since the interface's previous sample, then make
+    // this sample the new baseline. At zero elapsed time the baseline is kept,
+    // so the next sample measures the full span, and `None` is returned.
+    fn advance(
+        &mut self,
+        interface: &str,
+        current_rx: u64,
+        current_tx: u64,
+        current_time: Instant,
+    ) -> Option<((f64, f64), (u64, u64))> {
+        let speeds = self.compute_speeds(interface, current_rx, current_tx, current_time)?;
+        // Same deltas the speeds were computed from (0 after a counter reset)
+        let deltas = self
+            .previous_stats
+            .get(interface)
+            .map_or((0, 0), |(prev_rx, prev_tx, _)| {
+                (
+                    current_rx.saturating_sub(*prev_rx),
+                    current_tx.saturating_sub(*prev_tx),
+                )
+            });
+        self.previous_stats.insert(
+            interface.to_string(),
+            (current_rx, current_tx, current_time),
+        );
+        self.last_speeds.insert(interface.to_string(), speeds);
+        Some((speeds, deltas))
+    }
+
+    pub fn refresh(&mut self, show_virtual: bool, show_loopback: bool) -> Vec<NetworkStats> {
+        let samples = self.sample_counters();
+        self.last_sampled = samples.len();
+        self.empty_refreshes = if samples.is_empty() {
+            self.empty_refreshes.saturating_add(1)
+        } else {
+            0
+        };
         let current_time = Instant::now();
+        if self
+            .default_route_checked
+            .is_none_or(|checked| current_time.duration_since(checked) >= DEFAULT_ROUTE_TTL)
+        {
+            self.default_route = lookup_default_route();
+            self.default_route_checked = Some(current_time);
+        }
+        #[cfg(target_os = "windows")]
+        if self
+            .connections_checked
+            .is_none_or(|checked| current_time.duration_since(checked) >= CONNECTIONS_TTL)
+        {
+            self.connections = crate::network_windows::connections();
+            self.connections_checked = Some(current_time);
+        }
         let mut stats = Vec::new();
 
-        for (interface_name, data) in &self.networks {
-            let current_rx = data.total_received();
-            let current_tx = data.total_transmitted();
+        for (interface_name, current_rx, current_tx, packets) in samples {
+            let interface_name = &interface_name;
 
-            // Skip loopback interfaces
-            if interface_name.contains("Loopback") || interface_name == "lo" {
+            if !self.allowlist.is_empty() && !self.allowlist.contains(interface_name) {
                 continue;
             }
 
-            let (download_speed, upload_speed) =
-                self.compute_speeds(interface_name, current_rx, current_tx, current_time);
+            if !show_loopback && is_loopback_interface(interface_name) {
+                continue;
+            }
 
-            // Update previous stats for the next refresh
-            self.previous_stats.insert(
-                interface_name.clone(),
-                (current_rx, current_tx, current_time),
-            );
+            if !show_virtual && is_virtual_interface(interface_name) {
+                continue;
+            }
+
+            let (raw_speeds, (rx_delta, tx_delta), fresh) =
+                match self.advance(interface_name, current_rx, current_tx, current_time) {
+                    Some((speeds, deltas)) => (speeds, deltas, true),
+                    // Zero elapsed time: keep showing the last known speeds
+                    None => (
+                        self.last_speeds
+                            .get(interface_name)
+                            .copied()
+                            .unwrap_or((0.0, 0.0)),
+                        (0, 0),
+                        false,
+                    ),
+                };
+            let (raw_download_speed, raw_upload_speed) = raw_speeds;
+            let (download_speed, upload_speed) = self.smooth(interface_name, raw_speeds, fresh);
+
+            // Activity follows the raw speeds; a decaying average never reaches 0
+            if raw_download_speed > 0.0 || raw_upload_speed > 0.0 {
+                self.last_active
+                    .insert(interface_name.clone(), current_time);
+            }
+            let idle_time = self
+                .last_active
+                .get(interface_name)
+                .map(|t| current_time.duration_since(*t));
+            let is_active = idle_time.is_some_and(|idle| idle <= self.active_grace);
+
+            #[cfg(target_os = "linux")]
+            let (mtu, link_speed_mbps) = crate::network_linux::read_link_info(interface_name);
+            #[cfg(not(target_os = "linux"))]
+            let (mtu, link_speed_mbps) = (None, None);
+
+            // With the /proc/net/dev backend sysinfo's list isn't refreshed,
+            // so addresses are as of the last sysinfo refresh
+            let ip_addresses: Vec<IpAddr> = self
+                .networks
+                .get(interface_name)
+                .map(|data| data.ip_networks().iter().map(|n| n.addr).collect())
+                .unwrap_or_default();
+            let is_default = match &self.default_route {
+                #[cfg(not(target_os = "windows"))]
+                Some(DefaultRoute::Name(name)) => name == interface_name,
+                #[cfg(target_os = "windows")]
+                Some(DefaultRoute::Address(address)) => ip_addresses.contains(address),
+                None => false,
+            };
+            #[cfg(target_os = "windows")]
+            let connections = Some(count_connections(
+                &self.connections,
+                &ip_addresses,
+                self.connection_family,
+            ));
+            #[cfg(not(target_os = "windows"))]
+            let connections = None;
 
             stats.push(NetworkStats {
                 name: interface_name.clone(),
+                display_name: clean_interface_name(interface_name),
                 bytes_received: current_rx,
                 bytes_transmitted: current_tx,
                 download_speed,
                 upload_speed,
+                raw_download_speed,
+                raw_upload_speed,
+                rx_delta,
+                tx_delta,
+                is_active,
+                idle_time,
+                ip_addresses,
+                mac_address: self
+                    .networks
+                    .get(interface_name)
+                    .map(|data| data.mac_address())
+                    .filter(|mac| !mac.is_unspecified()),
+                packets,
+                mtu,
+                link_speed_mbps,
+                is_default,
+                connections,
             });
         }
 
+        disambiguate_display_names(&mut stats);
         stats
     }
 }
 
-pub fn format_bytes(bytes: f64) -> String {
-    const UNITS: &[&str] = &["B/s", "KB/s", "MB/s", "GB/s"];
-    let mut size = bytes;
+// Cleaned names can collide (two OS interfaces with the same friendly name).
+// Keep the first, by OS name, as is and number the others "Name (2)", "Name (3)"...
+// so they can be told apart. Per-interface state is keyed on the OS name, so
+// this only affects what's shown.
+fn disambiguate_display_names(stats: &mut [NetworkStats]) {
+    let mut by_display_name: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, s) in stats.iter().enumerate() {
+        by_display_name
+            .entry(s.display_name.clone())
+            .or_default()
+            .push(index);
+    }
+    for mut indices in by_display_name.into_values().filter(|i| i.len() > 1) {
+        indices.sort_by(|a, b| stats[*a].name.cmp(&stats[*b].name));
+        for (n, index) in indices.into_iter().enumerate().skip(1) {
+            let display_name = format!("{} ({})", stats[index].display_name, n + 1);
+            stats[index].display_name = display_name;
+        }
+    }
+}
+
+// Loopback interfaces: `lo` on Linux, `lo0` on macOS and
+// "Loopback Pseudo-Interface 1" on Windows
+pub fn is_loopback_interface(name: &str) -> bool {
+    name.contains("Loopback")
+        || name
+            .strip_prefix("lo")
+            .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+}
+
+// Heuristic for virtual interfaces created by containers, VMs and VPNs
+pub fn is_virtual_interface(name: &str) -> bool {
+    const PATTERNS: &[&str] = &[
+        "veth",
+        "docker",
+        "br-",
+        "virbr",
+        "vmnet",
+        "virtualbox host-only",
+        "hyper-v",
+        "vethernet",
+    ];
+    const PREFIXES: &[&str] = &["tun", "tap"];
+
+    #[cfg(target_os = "macos")]
+    if crate::network_macos::is_apple_virtual(name) {
+        return true;
+    }
+
+    let name = name.to_lowercase();
+    PATTERNS.iter().any(|p| name.contains(p)) || PREFIXES.iter().any(|p| name.starts_with(p))
+}
+
+// Rough kind of an interface, guessed from its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterfaceKind {
+    Loopback,
+    Virtual,
+    Wifi,
+    Wired,
+    Other,
+}
+
+pub fn classify_interface(name: &str) -> InterfaceKind {
+    // Checked first: "vEthernet" is a Hyper-V switch, not a cable
+    if is_loopback_interface(name) {
+        return InterfaceKind::Loopback;
+    }
+    if is_virtual_interface(name) {
+        return InterfaceKind::Virtual;
+    }
+    // macOS calls Wi-Fi and wired devices alike enN; the hardware port name
+    // ("Wi-Fi", "Thunderbolt Ethernet") is what tells them apart
+    #[cfg(target_os = "macos")]
+    return kind_from_label(&crate::network_macos::clean_interface_name(name), false);
+    #[cfg(not(target_os = "macos"))]
+    kind_from_label(name, true)
+}
+
+// Kind from a name or label. `device_prefixes` also trusts Linux-style device
+// names (wlan0, wlp2s0, eth0, enp3s0).
+fn kind_from_label(label: &str, device_prefixes: bool) -> InterfaceKind {
+    let label = label.to_lowercase();
+    let prefixed =
+        |prefixes: &[&str]| device_prefixes && prefixes.iter().any(|p| label.starts_with(p));
+    let contains = |words: &[&str]| words.iter().any(|w| label.contains(w));
+    if prefixed(&["wl"]) || contains(&["wi-fi", "wifi", "wireless"]) {
+        InterfaceKind::Wifi
+    } else if prefixed(&["eth", "en"]) || contains(&["ethernet"]) {
+        InterfaceKind::Wired
+    } else {
+        InterfaceKind::Other
+    }
+}
+
+// Name to show for an interface
+pub fn clean_interface_name(name: &str) -> String {
+    #[cfg(target_os = "macos")]
+    return crate::network_macos::clean_interface_name(name);
+
+    #[cfg(not(target_os = "macos"))]
+    name.to_string()
+}
+
+const RATE_UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+const BIT_RATE_UNITS: &[&str] = &["b", "Kb", "Mb", "Gb", "Tb"];
+
+// Time base rates are displayed in. Speeds are always computed in bytes per
+// second; only the display is rescaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBase {
+    Second,
+    Minute,
+    Hour,
+}
+
+impl TimeBase {
+    pub fn multiplier(self) -> f64 {
+        match self {
+            TimeBase::Second => 1.0,
+            TimeBase::Minute => 60.0,
+            TimeBase::Hour => 3600.0,
+        }
+    }
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            TimeBase::Second => "/s",
+            TimeBase::Minute => "/min",
+            TimeBase::Hour => "/hr",
+        }
+    }
+}
+
+// Whether rates are shown in bytes (MB/s) or bits (Mbps)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedUnit {
+    Bytes,
+    Bits,
+}
+
+// Step between units: binary (1 KB = 1024 B) or SI (1 KB = 1000 B, as ISPs
+// quote speeds). The unit labels are the same either way. The automatic
+// precision drops decimals from 100 up, so 1,000,000 bytes is "977 KB" in
+// binary; "976.56 KB" needs a fixed precision of 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum UnitBase {
+    #[default]
+    Binary,
+    Si,
+}
+
+impl UnitBase {
+    fn divisor(self) -> f64 {
+        match self {
+            UnitBase::Binary => 1024.0,
+            UnitBase::Si => 1000.0,
+        }
+    }
+}
+
+// How numbers are written, for both rates and totals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NumberFormat {
+    // Decimal places; `None` picks 0, 1 or 2 depending on the magnitude
+    pub precision: Option<usize>,
+    pub base: UnitBase,
+}
+
+// How rates are displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateFormat {
+    pub time_base: TimeBase,
+    pub unit: SpeedUnit,
+    pub number: NumberFormat,
+}
+
+impl Default for RateFormat {
+    fn default() -> Self {
+        Self {
+            time_base: TimeBase::Second,
+            unit: SpeedUnit::Bytes,
+            number: NumberFormat::default(),
+        }
+    }
+}
+
+impl RateFormat {
+    // Displayed quantity for a rate in bytes per second
+    fn scale(&self, bytes: f64) -> f64 {
+        let bits = match self.unit {
+            SpeedUnit::Bytes => 1.0,
+            SpeedUnit::Bits => 8.0,
+        };
+        bytes * bits * self.time_base.multiplier()
+    }
+
+    fn unit_label(&self, unit_index: usize) -> String {
+        match (self.unit, self.time_base) {
+            (SpeedUnit::Bytes, time_base) => {
+                format!("{}{}", RATE_UNITS[unit_index], time_base.suffix())
+            }
+            // Network convention: "Mbps" rather than "Mb/s"
+            (SpeedUnit::Bits, TimeBase::Second) => format!("{}ps", BIT_RATE_UNITS[unit_index]),
+            (SpeedUnit::Bits, time_base) => {
+                format!("{}{}", BIT_RATE_UNITS[unit_index], time_base.suffix())
+            }
+        }
+    }
+}
+
+// NaN and infinite rates show as "—", negative ones as 0.
+pub fn format_bytes(bytes: f64, format: RateFormat) -> String {
+    if !bytes.is_finite() {
+        return "—".to_string();
+    }
+    let bytes = bytes.max(0.0);
+    let divisor = format.number.base.divisor();
+    let mut size = format.scale(bytes);
     let mut unit_index = 0;
 
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
+    while rounded(size, format.number) >= divisor && unit_index < RATE_UNITS.len() - 1 {
+        size /= divisor;
         unit_index += 1;
     }
 
-    if size >= 100.0 {
-        format!("{:.0} {}", size, UNITS[unit_index])
+    format_bytes_with_unit(bytes, unit_index, format)
+}
+
+// Format a rate (in bytes per second) in a fixed unit (index into B, KB, MB, GB, TB)
+pub fn format_bytes_with_unit(bytes: f64, unit_index: usize, format: RateFormat) -> String {
+    if !bytes.is_finite() {
+        return "—".to_string();
+    }
+    let bytes = bytes.max(0.0);
+    let unit_index = unit_index.min(RATE_UNITS.len() - 1);
+    let size = format.scale(bytes) / format.number.base.divisor().powi(unit_index as i32);
+    format_scaled(size, &format.unit_label(unit_index), format.number)
+}
+
+// Shared core of the formatters: `size` is already scaled to `unit`
+fn format_scaled(size: f64, unit: &str, format: NumberFormat) -> String {
+    let precision = decimals(size, format);
+    format!("{size:.precision$} {unit}")
+}
+
+fn decimals(size: f64, format: NumberFormat) -> usize {
+    format.precision.unwrap_or(if size >= 100.0 {
+        0
     } else if size >= 10.0 {
-        format!("{:.1} {}", size, UNITS[unit_index])
+        1
     } else {
-        format!("{:.2} {}", size, UNITS[unit_index])
+        2
+    })
+}
+
+// `size` as it will be printed. Picking units by this rather than the raw
+// size keeps rounding from carrying past a unit (1023.9 B showing as "1024 B").
+fn rounded(size: f64, format: NumberFormat) -> f64 {
+    let factor = 10f64.powi(decimals(size, format) as i32);
+    (size * factor).round() / factor
+}
+
+// Pick the unit for a rate (in bytes per second) given the unit currently shown.
+// Stepping up requires exceeding the boundary by 10% and stepping down requires
+// dropping 10% below it, so values hovering around 1024 don't flap between units.
+pub fn sticky_rate_unit(bytes: f64, current: usize, format: RateFormat) -> usize {
+    let divisor = format.number.base.divisor();
+    let size = format.scale(bytes);
+    let mut unit = current.min(RATE_UNITS.len() - 1);
+    while unit < RATE_UNITS.len() - 1 && size > divisor.powi(unit as i32 + 1) * 1.1 {
+        unit += 1;
+    }
+    while unit > 0 && size < divisor.powi(unit as i32) * 0.9 {
+        unit -= 1;
     }
+    unit
 }
 
-pub fn format_total_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+pub fn format_total_bytes(bytes: u64, format: NumberFormat) -> String {
+    let divisor = format.base.divisor();
     let mut size = bytes as f64;
     let mut unit_index = 0;
 
-    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-        size /= 1024.0;
+    while rounded(size, format) >= divisor && unit_index < RATE_UNITS.len() - 1 {
+        size /= divisor;
         unit_index += 1;
     }
 
-    if size >= 100.0 {
-        format!("{:.0} {}", size, UNITS[unit_index])
-    } else if size >= 10.0 {
-        format!("{:.1} {}", size, UNITS[unit_index])
-    } else {
-        format!("{:.2} {}", size, UNITS[unit_index])
+    format_scaled(size, RATE_UNITS[unit_index], format)
+}
+
+// Exact byte count with thousands separators, e.g. "12,345,678 bytes"
+pub fn format_exact_bytes(bytes: u64) -> String {
+    let digits = bytes.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{grouped} bytes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A monitor whose baseline is only the given synthetic counters
+    fn monitor_with(samples: &[(&str, u64, u64)], now: Instant) -> NetworkMonitor {
+        let mut monitor = NetworkMonitor::new(Backend::Sysinfo);
+        monitor.seed_from(
+            samples
+                .iter()
+                .map(|(name, rx, tx)| (name.to_string(), *rx, *tx)),
+            now,
+        );
+        monitor
+    }
+
+    #[test]
+    fn zero_elapsed_time_keeps_baseline() {
+        let t0 = Instant::now();
+        let mut monitor = monitor_with(&[("test0", 1_000, 2_000)], t0);
+
+        assert_eq!(monitor.advance("test0", 5_000, 6_000, t0), None);
+        assert_eq!(
+            monitor.previous_stats.get("test0"),
+            Some(&(1_000, 2_000, t0))
+        );
+
+        // The next sample measures from the kept baseline: the full delta
+        let (speeds, deltas) = monitor
+            .advance("test0", 5_000, 6_000, t0 + Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(speeds, (2_000.0, 2_000.0));
+        assert_eq!(deltas, (4_000, 4_000));
+    }
+
+    #[test]
+    fn rate_from_two_samples() {
+        let t0 = Instant::now();
+        let mut monitor = monitor_with(&[("test0", 1_000, 10_000)], t0);
+
+        let (speeds, deltas) = monitor
+            .advance("test0", 3_000, 11_000, t0 + Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(speeds, (1_000.0, 500.0));
+        assert_eq!(deltas, (2_000, 1_000));
+    }
+
+    #[test]
+    fn counter_reset_reports_zero_and_rebaselines() {
+        let t0 = Instant::now();
+        let t1 = t0 + Duration::from_secs(1);
+        let mut monitor = monitor_with(&[("test0", 1_000_000, 0)], t0);
+
+        let (speeds, deltas) = monitor.advance("test0", 500, 0, t1).unwrap();
+        assert_eq!(speeds, (0.0, 0.0));
+        assert_eq!(deltas, (0, 0));
+        assert_eq!(monitor.previous_stats.get("test0"), Some(&(500, 0, t1)));
+    }
+
+    fn with_precision(precision: Option<usize>) -> RateFormat {
+        RateFormat {
+            number: NumberFormat {
+                precision,
+                ..NumberFormat::default()
+            },
+            ..RateFormat::default()
+        }
+    }
+
+    #[test]
+    fn format_bytes_boundaries() {
+        let auto = RateFormat::default();
+        // Would round up to "1024 B/s"; shown in the next unit instead
+        assert_eq!(format_bytes(1023.9, auto), "1.00 KB/s");
+        assert_eq!(format_bytes(1024.0, auto), "1.00 KB/s");
+        assert_eq!(format_bytes(999_999.0, auto), "977 KB/s");
+
+        assert_eq!(format_bytes(1023.9, with_precision(Some(2))), "1023.90 B/s");
+        assert_eq!(format_bytes(1023.9, with_precision(Some(0))), "1 KB/s");
+        assert_eq!(
+            format_bytes(999_999.0, with_precision(Some(1))),
+            "976.6 KB/s"
+        );
+    }
+
+    #[test]
+    fn si_and_binary_units() {
+        let si = NumberFormat {
+            base: UnitBase::Si,
+            ..NumberFormat::default()
+        };
+        let binary = NumberFormat::default();
+        assert_eq!(format_total_bytes(1_000_000, si), "1.00 MB");
+        assert_eq!(format_total_bytes(1_000_000, binary), "977 KB");
+        assert_eq!(
+            format_total_bytes(
+                1_000_000,
+                NumberFormat {
+                    precision: Some(2),
+                    ..binary
+                }
+            ),
+            "976.56 KB"
+        );
+
+        let si_rate = RateFormat {
+            number: si,
+            ..RateFormat::default()
+        };
+        assert_eq!(format_bytes(1_000_000.0, si_rate), "1.00 MB/s");
+        assert_eq!(format_bytes_with_unit(1_000_000.0, 1, si_rate), "1000 KB/s");
+    }
+
+    #[test]
+    fn format_bytes_rejects_bad_values() {
+        let format = RateFormat::default();
+        assert_eq!(format_bytes(f64::NAN, format), "—");
+        assert_eq!(format_bytes(f64::INFINITY, format), "—");
+        assert_eq!(format_bytes(-5.0, format), "0.00 B/s");
+        assert_eq!(format_bytes_with_unit(f64::NAN, 2, format), "—");
+        assert_eq!(format_bytes_with_unit(-5.0, 2, format), "0.00 MB/s");
+    }
+
+    #[test]
+    fn exact_bytes_grouping() {
+        assert_eq!(format_exact_bytes(0), "0 bytes");
+        assert_eq!(format_exact_bytes(999), "999 bytes");
+        assert_eq!(format_exact_bytes(1000), "1,000 bytes");
+        assert_eq!(
+            format_exact_bytes(u64::MAX),
+            "18,446,744,073,709,551,615 bytes"
+        );
+    }
+
+    #[test]
+    fn total_bytes_edges() {
+        let format = NumberFormat::default();
+        assert_eq!(format_total_bytes(0, format), "0.00 B");
+        assert_eq!(format_total_bytes(999, format), "999 B");
+        assert_eq!(format_total_bytes(1000, format), "1000 B");
+        // Past TB it stays in TB
+        assert_eq!(format_total_bytes(u64::MAX, format), "16777216 TB");
+    }
+
+    #[test]
+    fn time_base_scaling_and_suffix() {
+        let per = |time_base, unit| RateFormat {
+            time_base,
+            unit,
+            ..RateFormat::default()
+        };
+        // 1 KB/s is 60 KB/min and 3.5 MB/hr
+        assert_eq!(
+            format_bytes(1024.0, per(TimeBase::Second, SpeedUnit::Bytes)),
+            "1.00 KB/s"
+        );
+        assert_eq!(
+            format_bytes(1024.0, per(TimeBase::Minute, SpeedUnit::Bytes)),
+            "60.0 KB/min"
+        );
+        assert_eq!(
+            format_bytes(1024.0, per(TimeBase::Hour, SpeedUnit::Bytes)),
+            "3.52 MB/hr"
+        );
+        assert_eq!(
+            format_bytes(1024.0, per(TimeBase::Second, SpeedUnit::Bits)),
+            "8.00 Kbps"
+        );
+        assert_eq!(
+            format_bytes(1024.0, per(TimeBase::Minute, SpeedUnit::Bits)),
+            "480 Kb/min"
+        );
+        assert_eq!(
+            format_scaled(1.5, "MB/hr", NumberFormat::default()),
+            "1.50 MB/hr"
+        );
+    }
+
+    #[test]
+    fn sticky_unit_needs_a_margin() {
+        let format = RateFormat::default();
+        // Just over 1 KB/s isn't enough to leave bytes...
+        assert_eq!(sticky_rate_unit(1100.0, 0, format), 0);
+        assert_eq!(sticky_rate_unit(1200.0, 0, format), 1);
+        // ...nor just under to leave KB
+        assert_eq!(sticky_rate_unit(1000.0, 1, format), 1);
+        assert_eq!(sticky_rate_unit(900.0, 1, format), 0);
+        // Per minute scales before the unit is picked
+        let per_minute = RateFormat {
+            time_base: TimeBase::Minute,
+            ..format
+        };
+        assert_eq!(sticky_rate_unit(1024.0, 0, per_minute), 1);
+    }
+
+    fn stats_named(name: &str, display_name: &str) -> NetworkStats {
+        NetworkStats {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            bytes_received: 0,
+            bytes_transmitted: 0,
+            download_speed: 0.0,
+            upload_speed: 0.0,
+            raw_download_speed: 0.0,
+            raw_upload_speed: 0.0,
+            rx_delta: 0,
+            tx_delta: 0,
+            is_active: false,
+            idle_time: None,
+            ip_addresses: Vec::new(),
+            mac_address: None,
+            packets: PacketCounters::default(),
+            mtu: None,
+            link_speed_mbps: None,
+            is_default: false,
+            connections: None,
+        }
+    }
+
+    #[test]
+    fn colliding_display_names_are_numbered() {
+        let mut stats = vec![
+            stats_named("{C}", "Ethernet"),
+            stats_named("{A}", "Ethernet"),
+            stats_named("{D}", "Wi-Fi"),
+            stats_named("{B}", "Ethernet"),
+        ];
+        disambiguate_display_names(&mut stats);
+        let names: Vec<&str> = stats.iter().map(|s| s.display_name.as_str()).collect();
+        // Numbered in order of the OS name; the first keeps its name
+        assert_eq!(names, ["Ethernet (3)", "Ethernet", "Wi-Fi", "Ethernet (2)"]);
+    }
+
+    #[test]
+    fn loopback_names() {
+        assert!(is_loopback_interface("lo"));
+        assert!(is_loopback_interface("lo0"));
+        assert!(is_loopback_interface("Loopback Pseudo-Interface 1"));
+        assert!(!is_loopback_interface("lowpan0"));
+        assert!(!is_loopback_interface("eth0"));
+        assert!(!is_loopback_interface("wlo1"));
+    }
+
+    #[test]
+    fn interface_kinds() {
+        assert_eq!(classify_interface("lo"), InterfaceKind::Loopback);
+        assert_eq!(classify_interface("docker0"), InterfaceKind::Virtual);
+        assert_eq!(
+            classify_interface("vEthernet (WSL)"),
+            InterfaceKind::Virtual
+        );
+
+        for name in ["wlan0", "wlp2s0", "Wi-Fi", "Wireless Network Connection"] {
+            assert_eq!(kind_from_label(name, true), InterfaceKind::Wifi, "{name}");
+        }
+        for name in ["eth0", "enp3s0", "eno1", "Ethernet 2"] {
+            assert_eq!(kind_from_label(name, true), InterfaceKind::Wired, "{name}");
+        }
+        assert_eq!(kind_from_label("tailscale0", true), InterfaceKind::Other);
+
+        // Hardware port names on macOS, where device names prove nothing
+        assert_eq!(kind_from_label("en0", false), InterfaceKind::Other);
+        assert_eq!(kind_from_label("Wi-Fi", false), InterfaceKind::Wifi);
+        assert_eq!(
+            kind_from_label("Thunderbolt Ethernet", false),
+            InterfaceKind::Wired
+        );
+    }
+
+    #[test]
+    fn connections_are_attributed_by_local_address() {
+        let ethernet: IpAddr = "192.168.1.10".parse().unwrap();
+        let ethernet_v6: IpAddr = "fe80::1".parse().unwrap();
+        let wifi: IpAddr = "10.0.0.5".parse().unwrap();
+        let connections = [
+            (Protocol::Tcp, ethernet),
+            (Protocol::Tcp, ethernet),
+            (Protocol::Tcp, ethernet_v6),
+            (Protocol::Udp, ethernet),
+            (Protocol::Tcp, wifi),
+            (Protocol::Udp, wifi),
+            (Protocol::Udp, wifi),
+        ];
+        let addresses = [ethernet, ethernet_v6];
+
+        assert_eq!(
+            count_connections(&connections, &addresses, AddressFamily::Both),
+            (3, 1)
+        );
+        assert_eq!(
+            count_connections(&connections, &addresses, AddressFamily::V4),
+            (2, 1)
+        );
+        assert_eq!(
+            count_connections(&connections, &addresses, AddressFamily::V6),
+            (1, 0)
+        );
+        assert_eq!(
+            count_connections(&connections, &[wifi], AddressFamily::Both),
+            (1, 2)
+        );
+        assert_eq!(
+            count_connections(&connections, &[], AddressFamily::Both),
+            (0, 0)
+        );
+    }
+
+    // Drives the real backend end to end, so a change to `refresh`'s
+    // signature or filtering breaks here rather than only in main.rs
+    #[test]
+    fn refresh_filters_the_host_interfaces() {
+        let mut monitor = NetworkMonitor::new(Backend::Sysinfo);
+        let everything = monitor.refresh(true, true);
+        let physical = monitor.refresh(false, false);
+
+        assert!(physical.len() <= everything.len());
+        for stats in &physical {
+            assert!(!is_loopback_interface(&stats.name), "{}", stats.name);
+            assert!(!is_virtual_interface(&stats.name), "{}", stats.name);
+            assert!(stats.download_speed >= 0.0 && stats.upload_speed >= 0.0);
+        }
     }
 }
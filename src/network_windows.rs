@@ -0,0 +1,130 @@
+// Windows-specific interface information, read through the IP Helper API.
+// These calls are cheap (no process is spawned) and report states as numbers,
+// so they work the same on every Windows display language.
+use std::ffi::c_void;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use windows_sys::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, NO_ERROR};
+use windows_sys::Win32::NetworkManagement::IpHelper::{
+    GetExtendedTcpTable, GetExtendedUdpTable, GetIpAddrTable, GetIpForwardTable, MIB_IPADDRROW_XP,
+    MIB_IPADDRTABLE, MIB_IPFORWARDROW, MIB_IPFORWARDTABLE, MIB_TCP_STATE_ESTAB,
+    MIB_TCP6ROW_OWNER_PID, MIB_TCP6TABLE_OWNER_PID, MIB_TCPROW_OWNER_PID, MIB_TCPTABLE_OWNER_PID,
+    MIB_UDP6ROW_OWNER_PID, MIB_UDP6TABLE_OWNER_PID, MIB_UDPROW_OWNER_PID, MIB_UDPTABLE_OWNER_PID,
+    TCP_TABLE_OWNER_PID_ALL, UDP_TABLE_OWNER_PID,
+};
+use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+
+use crate::network_monitor::Protocol;
+
+// Calls an IP Helper "get table" function: it's passed a buffer and its size
+// in bytes, and asks for a bigger one if the table doesn't fit. Retried a few
+// times since the table can grow between calls. The buffer is made of u64s so
+// the table structs are suitably aligned.
+fn read_table(get: impl Fn(*mut c_void, *mut u32) -> u32) -> Option<Vec<u64>> {
+    let mut size = 0u32;
+    for _ in 0..4 {
+        let mut buffer = vec![0u64; (size as usize).div_ceil(8).max(1)];
+        size = (buffer.len() * 8) as u32;
+        match get(buffer.as_mut_ptr().cast(), &mut size) {
+            NO_ERROR => return Some(buffer),
+            ERROR_INSUFFICIENT_BUFFER => continue,
+            _ => return None,
+        }
+    }
+    None
+}
+
+// The rows of a table read by `read_table`, which all start with a row count
+// followed by that many rows.
+macro_rules! rows {
+    ($buffer:expr, $table:ty, $row:ty) => {{
+        let table = $buffer.as_ptr().cast::<$table>();
+        // SAFETY: the API filled the buffer with a $table holding
+        // dwNumEntries rows, and the buffer outlives the returned slice
+        unsafe {
+            std::slice::from_raw_parts(
+                std::ptr::addr_of!((*table).table).cast::<$row>(),
+                (*table).dwNumEntries as usize,
+            )
+        }
+    }};
+}
+
+// Addresses in the tables are in network byte order
+fn ipv4(address: u32) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::from(address.to_ne_bytes()))
+}
+
+fn ipv6(address: [u8; 16]) -> IpAddr {
+    IpAddr::V6(Ipv6Addr::from(address))
+}
+
+// Local address of the interface carrying the IPv4 default route. With
+// several default routes, the one with the lowest metric wins. Adapter names
+// don't match sysinfo's, so callers match the address against each
+// interface's addresses instead.
+pub fn default_route_address() -> Option<IpAddr> {
+    let routes = read_table(|buffer, size| unsafe { GetIpForwardTable(buffer.cast(), size, 0) })?;
+    let index = rows!(routes, MIB_IPFORWARDTABLE, MIB_IPFORWARDROW)
+        .iter()
+        .filter(|row| row.dwForwardDest == 0 && row.dwForwardMask == 0)
+        .min_by_key(|row| row.dwForwardMetric1)?
+        .dwForwardIfIndex;
+
+    let addresses = read_table(|buffer, size| unsafe { GetIpAddrTable(buffer.cast(), size, 0) })?;
+    rows!(addresses, MIB_IPADDRTABLE, MIB_IPADDRROW_XP)
+        .iter()
+        .find(|row| row.dwIndex == index && row.dwAddr != 0)
+        .map(|row| ipv4(row.dwAddr))
+}
+
+// Protocol and local address of open connections, one entry per connection:
+// established TCP connections and UDP sockets bound to a specific address.
+// Wildcard binds (0.0.0.0, [::]) aren't tied to any one interface, so they're
+// left out.
+pub fn connections() -> Vec<(Protocol, IpAddr)> {
+    let tcp = |family: u16| {
+        read_table(|buffer, size| unsafe {
+            GetExtendedTcpTable(buffer, size, 0, family.into(), TCP_TABLE_OWNER_PID_ALL, 0)
+        })
+    };
+    let udp = |family: u16| {
+        read_table(|buffer, size| unsafe {
+            GetExtendedUdpTable(buffer, size, 0, family.into(), UDP_TABLE_OWNER_PID, 0)
+        })
+    };
+    let established = MIB_TCP_STATE_ESTAB as u32;
+
+    let mut connections = Vec::new();
+    if let Some(table) = tcp(AF_INET) {
+        connections.extend(
+            rows!(table, MIB_TCPTABLE_OWNER_PID, MIB_TCPROW_OWNER_PID)
+                .iter()
+                .filter(|row| row.dwState == established)
+                .map(|row| (Protocol::Tcp, ipv4(row.dwLocalAddr))),
+        );
+    }
+    if let Some(table) = tcp(AF_INET6) {
+        connections.extend(
+            rows!(table, MIB_TCP6TABLE_OWNER_PID, MIB_TCP6ROW_OWNER_PID)
+                .iter()
+                .filter(|row| row.dwState == established)
+                .map(|row| (Protocol::Tcp, ipv6(row.ucLocalAddr))),
+        );
+    }
+    if let Some(table) = udp(AF_INET) {
+        connections.extend(
+            rows!(table, MIB_UDPTABLE_OWNER_PID, MIB_UDPROW_OWNER_PID)
+                .iter()
+                .map(|row| (Protocol::Udp, ipv4(row.dwLocalAddr))),
+        );
+    }
+    if let Some(table) = udp(AF_INET6) {
+        connections.extend(
+            rows!(table, MIB_UDP6TABLE_OWNER_PID, MIB_UDP6ROW_OWNER_PID)
+                .iter()
+                .map(|row| (Protocol::Udp, ipv6(row.ucLocalAddr))),
+        );
+    }
+    connections.retain(|(_protocol, address)| !address.is_unspecified());
+    connections
+}
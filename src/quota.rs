@@ -0,0 +1,232 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Data used against a monthly quota. Usage is only counted while speedy is
+// running: traffic while it's closed (or paused) is never seen, so the total
+// is a lower bound on what the provider will bill.
+#[derive(Debug, Clone)]
+pub struct Quota {
+    pub limit_gb: f64,  // 0 = quota tracking off
+    pub start_day: u32, // day of the month the billing cycle starts, 1-31
+    pub used: u64,      // bytes counted in the current cycle
+    pub cycle_start: Option<Date>,
+}
+
+impl Default for Quota {
+    fn default() -> Self {
+        Self {
+            limit_gb: 0.0,
+            start_day: 1,
+            used: 0,
+            cycle_start: None,
+        }
+    }
+}
+
+impl Quota {
+    pub fn enabled(&self) -> bool {
+        self.limit_gb > 0.0
+    }
+
+    pub fn limit_bytes(&self) -> u64 {
+        (self.limit_gb * 1024.0 * 1024.0 * 1024.0) as u64
+    }
+
+    // Fraction of the quota used; above 1 once it's exceeded
+    pub fn fraction_used(&self) -> f64 {
+        match self.limit_bytes() {
+            0 => 0.0,
+            limit => self.used as f64 / limit as f64,
+        }
+    }
+
+    // Count `bytes` towards the cycle containing `today`, starting over first
+    // if a new cycle has begun since the last call.
+    pub fn add(&mut self, today: Date, bytes: u64) {
+        let cycle_start = today.cycle_start(self.start_day);
+        if self.cycle_start != Some(cycle_start) {
+            self.cycle_start = Some(cycle_start);
+            self.used = 0;
+        }
+        self.used = self.used.saturating_add(bytes);
+    }
+
+    // Move the cycle start day without losing the usage counted so far: the
+    // current cycle is re-dated rather than ended. A cycle that had already
+    // ended (e.g. while tracking was off) is dropped first, as `add` would.
+    pub fn set_start_day(&mut self, start_day: u32, today: Date) {
+        if self
+            .cycle_start
+            .is_some_and(|start| start != today.cycle_start(self.start_day))
+        {
+            self.used = 0;
+        }
+        self.start_day = start_day;
+        if self.cycle_start.is_some() {
+            self.cycle_start = Some(today.cycle_start(start_day));
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.used = 0;
+    }
+}
+
+// A calendar date
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32, // 1-12
+    pub day: u32,   // 1-31
+}
+
+impl Date {
+    // Today in the local time zone, so cycles roll over at local midnight.
+    // Falls back to UTC if the local time can't be read.
+    pub fn today() -> Self {
+        Self::local_today().unwrap_or_else(|| {
+            let secs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            Self::from_days_since_epoch((secs / 86_400) as i64)
+        })
+    }
+
+    #[cfg(unix)]
+    fn local_today() -> Option<Self> {
+        // SAFETY: localtime_r only writes to the `tm` it's given
+        let tm = unsafe {
+            let now = libc::time(std::ptr::null_mut());
+            let mut tm = std::mem::zeroed::<libc::tm>();
+            libc::localtime_r(&now, &mut tm).as_ref().copied()
+        }?;
+        Some(Self {
+            year: tm.tm_year + 1900,
+            month: (tm.tm_mon + 1) as u32,
+            day: tm.tm_mday as u32,
+        })
+    }
+
+    #[cfg(windows)]
+    fn local_today() -> Option<Self> {
+        let mut now = windows_sys::Win32::Foundation::SYSTEMTIME::default();
+        // SAFETY: GetLocalTime only writes to the SYSTEMTIME it's given
+        unsafe { windows_sys::Win32::System::SystemInformation::GetLocalTime(&mut now) };
+        Some(Self {
+            year: i32::from(now.wYear),
+            month: u32::from(now.wMonth),
+            day: u32::from(now.wDay),
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn local_today() -> Option<Self> {
+        None
+    }
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+
+    // First day of the billing cycle containing this date. A start day past
+    // the end of a short month falls on that month's last day.
+    pub fn cycle_start(self, start_day: u32) -> Self {
+        let this_month = start_day.clamp(1, days_in_month(self.year, self.month));
+        if self.day >= this_month {
+            return Self {
+                day: this_month,
+                ..self
+            };
+        }
+        let (year, month) = if self.month == 1 {
+            (self.year - 1, 12)
+        } else {
+            (self.year, self.month - 1)
+        };
+        Self {
+            year,
+            month,
+            day: start_day.clamp(1, days_in_month(year, month)),
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    // YYYY-MM-DD
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid date: {s}");
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().ok_or_else(invalid);
+        let year = next()?.parse().map_err(|_| invalid())?;
+        let month: u32 = next()?.parse().map_err(|_| invalid())?;
+        let day: u32 = next()?.parse().map_err(|_| invalid())?;
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return Err(invalid());
+        }
+        Ok(Self { year, month, day })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> Date {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn changing_start_day_keeps_usage() {
+        let mut quota = Quota::default();
+        quota.add(date("2026-10-20"), 1000);
+        assert_eq!(quota.cycle_start, Some(date("2026-10-01")));
+
+        quota.set_start_day(15, date("2026-10-20"));
+        assert_eq!(quota.cycle_start, Some(date("2026-10-15")));
+        quota.add(date("2026-10-20"), 500);
+        assert_eq!(quota.used, 1500);
+
+        // A real rollover still starts over
+        quota.add(date("2026-11-15"), 7);
+        assert_eq!(quota.used, 7);
+    }
+
+    #[test]
+    fn changing_start_day_drops_a_stale_cycle() {
+        let mut quota = Quota::default();
+        quota.add(date("2026-09-20"), 1000);
+
+        // Tracking was off over the October rollover
+        quota.set_start_day(15, date("2026-10-20"));
+        assert_eq!(quota.cycle_start, Some(date("2026-10-15")));
+        assert_eq!(quota.used, 0);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::SortMode;
+use crate::network_monitor::{SpeedUnit, UnitBase};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Bump this whenever the meaning of a stored key changes, and teach `migrate`
+// how to upgrade settings written by older versions.
+pub const SETTINGS_VERSION: u32 = 1;
+pub const VERSION_KEY: &str = "speedy.settings_version";
+
+// In-memory key/value store, used to gather settings for export and to apply
+// imported ones through the same load path as eframe's storage.
+#[derive(Default)]
+pub struct MemoryStorage {
+    values: BTreeMap<String, String>,
+}
+
+impl eframe::Storage for MemoryStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.values.insert(key.to_string(), value);
+    }
+
+    fn flush(&mut self) {}
+}
+
+// Read and parse a stored value, ignoring missing or malformed entries
+pub fn load<T: FromStr>(storage: &dyn eframe::Storage, key: &str) -> Option<T> {
+    storage.get_string(key).and_then(|v| v.parse().ok())
+}
+
+#[derive(Serialize, Deserialize)]
+struct SettingsFile {
+    version: u32,
+    settings: BTreeMap<String, String>,
+}
+
+// Upgrade settings written by an older version in place. Version 0 (before
+// versioning) only stored the sort mode, whose format hasn't changed, so there
+// is nothing to convert yet.
+pub fn migrate(from: u32, storage: &mut dyn eframe::Storage) {
+    if from < SETTINGS_VERSION {
+        storage.set_string(VERSION_KEY, SETTINGS_VERSION.to_string());
+    }
+}
+
+// Default location for exported files: next to the executable, falling back
+// to the working directory.
+pub fn default_export_path(file_name: &str) -> PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(file_name)))
+        .unwrap_or_else(|| PathBuf::from(file_name))
+}
+
+// Bump this when a field of `Config` changes meaning
+pub const CONFIG_VERSION: u32 = 1;
+
+// Human-editable `config.json`: the settings people are likely to tweak by
+// hand. Every field is optional, so a file listing only some of them leaves
+// the rest as they were. Values go through the same storage keys as
+// everything else, so they're validated by the usual load path.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    pub sort_mode: Option<SortMode>, // "Name", "Download", "Upload" or "Total"
+    pub sort_descending: Option<bool>,
+    pub speed_unit: Option<SpeedUnit>, // "Bytes" or "Bits"
+    pub unit_base: Option<UnitBase>,   // "Binary" or "Si"
+    pub interval_ms: Option<u64>,
+    pub always_on_top: Option<bool>,
+    pub hidden_interfaces: Option<Vec<String>>,
+    pub alert_threshold: Option<f64>, // bytes per second
+    pub medium_speed: Option<f64>,    // bytes per second
+    pub fast_speed: Option<f64>,      // bytes per second
+    pub total_warn_bytes: Option<u64>,
+    pub total_high_bytes: Option<u64>,
+}
+
+impl Config {
+    // The config's fields, picked out of a full set of settings
+    pub fn from_storage(storage: &dyn eframe::Storage) -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            sort_mode: load_variant(storage, crate::STORAGE_KEY),
+            sort_descending: load(storage, crate::SORT_DESCENDING_KEY),
+            speed_unit: load_variant(storage, crate::SPEED_UNIT_KEY),
+            unit_base: load_variant(storage, crate::UNIT_BASE_KEY),
+            interval_ms: load(storage, crate::INTERVAL_KEY),
+            always_on_top: load(storage, crate::ALWAYS_ON_TOP_KEY),
+            hidden_interfaces: storage
+                .get_string(crate::HIDDEN_INTERFACES_KEY)
+                .map(|hidden| {
+                    hidden
+                        .split(',')
+                        .filter(|name| !name.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }),
+            alert_threshold: load(storage, crate::ALERT_THRESHOLD_KEY),
+            medium_speed: load(storage, crate::MEDIUM_SPEED_KEY),
+            fast_speed: load(storage, crate::FAST_SPEED_KEY),
+            total_warn_bytes: load(storage, crate::TOTAL_WARN_KEY),
+            total_high_bytes: load(storage, crate::TOTAL_HIGH_KEY),
+        }
+    }
+
+    // The fields that are set, as storage entries for `load_settings`
+    pub fn to_storage(&self) -> MemoryStorage {
+        let mut storage = MemoryStorage::default();
+        let mut set = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                storage.values.insert(key.to_string(), value);
+            }
+        };
+        set(
+            crate::STORAGE_KEY,
+            self.sort_mode.as_ref().and_then(variant),
+        );
+        set(
+            crate::SORT_DESCENDING_KEY,
+            self.sort_descending.map(|v| v.to_string()),
+        );
+        set(
+            crate::SPEED_UNIT_KEY,
+            self.speed_unit.as_ref().and_then(variant),
+        );
+        set(
+            crate::UNIT_BASE_KEY,
+            self.unit_base.as_ref().and_then(variant),
+        );
+        set(crate::INTERVAL_KEY, self.interval_ms.map(|v| v.to_string()));
+        set(
+            crate::ALWAYS_ON_TOP_KEY,
+            self.always_on_top.map(|v| v.to_string()),
+        );
+        set(
+            crate::HIDDEN_INTERFACES_KEY,
+            self.hidden_interfaces.as_ref().map(|names| names.join(",")),
+        );
+        set(
+            crate::ALERT_THRESHOLD_KEY,
+            self.alert_threshold.map(|v| v.to_string()),
+        );
+        set(
+            crate::MEDIUM_SPEED_KEY,
+            self.medium_speed.map(|v| v.to_string()),
+        );
+        set(
+            crate::FAST_SPEED_KEY,
+            self.fast_speed.map(|v| v.to_string()),
+        );
+        set(
+            crate::TOTAL_WARN_KEY,
+            self.total_warn_bytes.map(|v| v.to_string()),
+        );
+        set(
+            crate::TOTAL_HIGH_KEY,
+            self.total_high_bytes.map(|v| v.to_string()),
+        );
+        storage
+    }
+}
+
+// Enum settings are stored under their variant names, which is also how serde
+// spells them, so serde converts between the two
+fn variant<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value)
+        .ok()?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn load_variant<T: DeserializeOwned>(storage: &dyn eframe::Storage, key: &str) -> Option<T> {
+    serde_json::from_value(serde_json::Value::String(storage.get_string(key)?)).ok()
+}
+
+// `config.json` in the data directory given with --data-dir, otherwise in the
+// platform config directory (e.g. ~/.config/speedy on Linux)
+pub fn config_path(data_dir: Option<&Path>) -> Option<PathBuf> {
+    match data_dir {
+        Some(dir) => Some(dir.join("config.json")),
+        None => directories::ProjectDirs::from("", "", "speedy")
+            .map(|dirs| dirs.config_dir().join("config.json")),
+    }
+}
+
+// The config file at `path`, or `None` if there isn't one
+pub fn read_config(path: &Path) -> Option<Result<Config, String>> {
+    path.exists().then(|| {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let config: Config =
+            serde_json::from_str(&json).map_err(|e| format!("Not a Speedy config file: {e}"))?;
+        if config.version > CONFIG_VERSION {
+            return Err(format!(
+                "Config was written by a newer version of Speedy (version {}, supported up to {})",
+                config.version, CONFIG_VERSION
+            ));
+        }
+        Ok(config)
+    })
+}
+
+pub fn write_config(config: &Config, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+pub fn export_to_file(settings: MemoryStorage, path: &Path) -> Result<(), String> {
+    let file = SettingsFile {
+        version: SETTINGS_VERSION,
+        settings: settings.values,
+    };
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+pub fn import_from_file(path: &Path) -> Result<MemoryStorage, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let file: SettingsFile =
+        serde_json::from_str(&json).map_err(|e| format!("Not a Speedy settings file: {e}"))?;
+    if file.version > SETTINGS_VERSION {
+        return Err(format!(
+            "Settings were exported by a newer version of Speedy (version {}, supported up to {})",
+            file.version, SETTINGS_VERSION
+        ));
+    }
+
+    let mut storage = MemoryStorage {
+        values: file.settings,
+    };
+    migrate(file.version, &mut storage);
+    Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::Storage;
+
+    #[test]
+    fn config_round_trips_through_storage() {
+        let config = Config {
+            version: CONFIG_VERSION,
+            sort_mode: Some(SortMode::Download),
+            unit_base: Some(UnitBase::Si),
+            sort_descending: Some(true),
+            interval_ms: Some(500),
+            hidden_interfaces: Some(vec!["docker0".to_string(), "veth1".to_string()]),
+            medium_speed: Some(1024.0),
+            fast_speed: Some(1_048_576.0),
+            ..Default::default()
+        };
+        assert_eq!(Config::from_storage(&config.to_storage()), config);
+
+        // Fields left out of the file stay unset rather than taking defaults
+        let partial: Config = serde_json::from_str(r#"{ "always_on_top": false }"#).unwrap();
+        assert_eq!(partial.always_on_top, Some(false));
+        assert_eq!(partial.sort_mode, None);
+        assert!(
+            partial
+                .to_storage()
+                .get_string(crate::INTERVAL_KEY)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn config_lives_in_the_data_dir_when_given() {
+        let dir = Path::new("portable");
+        assert_eq!(config_path(Some(dir)), Some(dir.join("config.json")));
+    }
+
+    #[test]
+    fn misspelled_config_values_are_rejected() {
+        let dir = std::env::temp_dir().join(format!("speedy-config-{}", std::process::id()));
+        let path = dir.join("config.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, r#"{ "version": 1, "sort_mode": "download" }"#).unwrap();
+        let result = read_config(&path);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(result, Some(Err(_))));
+    }
+}
@@ -0,0 +1,71 @@
+// System tray icon with the combined speed as its tooltip (Windows and macOS;
+// on Linux tray-icon needs GTK, which the app doesn't otherwise depend on).
+//
+// Threading: everything here runs on the main thread. eframe owns the
+// platform event loop there, and tray-icon hooks into that same loop, so the
+// tray must be created from inside the app (after the loop has started, which
+// macOS requires) rather than before `run_native`. Menu clicks arrive through
+// muda's global event handler, also on the main thread; it only forwards them
+// to egui as viewport commands and wakes the app with a repaint request, so
+// the window reacts even while it is minimized and `update` isn't running.
+use eframe::egui;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+pub struct Tray {
+    icon: TrayIcon,
+}
+
+impl Tray {
+    pub fn new(ctx: &egui::Context) -> Result<Self, String> {
+        let show = MenuItem::new("Show window", true, None);
+        let hide = MenuItem::new("Hide window", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+        let menu = Menu::new();
+        menu.append_items(&[&show, &hide, &PredefinedMenuItem::separator(), &quit])
+            .map_err(|e| e.to_string())?;
+
+        let (show_id, hide_id, quit_id) = (show.id().clone(), hide.id().clone(), quit.id().clone());
+        let ctx = ctx.clone();
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            // Minimizing rather than hiding: a hidden window stops receiving
+            // redraws on some platforms, so it couldn't process "Show" again
+            if event.id == show_id {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            } else if event.id == hide_id {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            } else if event.id == quit_id {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+            ctx.request_repaint();
+        }));
+
+        let icon = TrayIconBuilder::new()
+            .with_menu(Box::new(menu))
+            .with_tooltip("Speedy")
+            .with_icon(icon())
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { icon })
+    }
+
+    pub fn set_tooltip(&self, text: &str) {
+        let _ = self.icon.set_tooltip(Some(text));
+    }
+}
+
+// A plain blue dot, drawn here so no image file needs to be shipped
+fn icon() -> Icon {
+    const SIZE: u32 = 32;
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+            let alpha = if distance <= center { 255 } else { 0 };
+            rgba.extend_from_slice(&[20, 100, 200, alpha]);
+        }
+    }
+    Icon::from_rgba(rgba, SIZE, SIZE).expect("icon buffer matches its size")
+}